@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+use std::env::args;
+use std::fs;
+use std::path::PathBuf;
+
+use cw721_data::merkle::MerkleTree;
+
+/// Builds the whitelist merkle tree of an address list, one bech32 address per line.
+///
+/// Usage: cargo run --example merkle_tree -- <addresses file> [output dir]
+///
+/// Writes `merkle_root.txt` with the root to set in `InstantiateMsg::merkle_root`
+/// and `proofs.json` with the proof to send in `MintMsg::proof` for every address.
+fn main() {
+    let args: Vec<String> = args().collect();
+    if args.len() < 2 || args.len() > 3 {
+        eprintln!("Usage: merkle_tree <addresses file> [output dir]");
+        std::process::exit(1);
+    }
+    let out_dir = PathBuf::from(args.get(2).map(String::as_str).unwrap_or("."));
+
+    let input = fs::read_to_string(&args[1]).expect("Cannot read the address list");
+    let mut addresses: Vec<String> = input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect();
+    addresses.sort();
+    addresses.dedup();
+
+    let tree = MerkleTree::new(&addresses).expect("Cannot build the merkle tree");
+    let proofs: BTreeMap<&str, Vec<String>> = addresses
+        .iter()
+        .enumerate()
+        .map(|(index, address)| (address.as_str(), tree.proof(index)))
+        .collect();
+
+    fs::create_dir_all(&out_dir).unwrap();
+    fs::write(out_dir.join("merkle_root.txt"), format!("{}\n", tree.root())).unwrap();
+    fs::write(
+        out_dir.join("proofs.json"),
+        serde_json::to_string_pretty(&proofs).unwrap(),
+    )
+    .unwrap();
+
+    println!("merkle_root: {}", tree.root());
+    println!("{} proofs written to {}", proofs.len(), out_dir.display());
+}
@@ -5,9 +5,14 @@ use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, s
 
 use cw721::{
     AllNftInfoResponse, ApprovedForAllResponse, ContractInfoResponse, NftInfoResponse,
-    NumTokensResponse, OwnerOfResponse, TokensResponse,
+    NumTokensResponse, OwnerOfResponse, PlanetHookMsg, TokensResponse,
+};
+use cw721_data::{
+    AdminResponse, BalanceResponse, ExecuteMsg, Extension, HoldersResponse, HooksResponse,
+    InstantiateMsg, NftsDataPageResponse, NftsDataResponse, OperatorGrantsResponse,
+    PlanetLocationResponse, QueryMsg, RecentEditsResponse, SalePhasesResponse,
+    SpenderApprovalsResponse, SystemPlanetsResponse, WhitelistStatusResponse,
 };
-use cw721_data::{ExecuteMsg, Extension, InstantiateMsg, AdminResponse, QueryMsg};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -17,21 +22,46 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema_with_title(&schema_for!(ExecuteMsg<Extension>), &out_dir, "ExecuteMsg");
-    export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema_with_title(&schema_for!(QueryMsg), &out_dir, "QueryMsg");
     export_schema_with_title(
-        &schema_for!(AllNftInfoResponse),
+        &schema_for!(AllNftInfoResponse<Extension>),
         &out_dir,
         "AllNftInfoResponse",
     );
     export_schema(&schema_for!(ApprovedForAllResponse), &out_dir);
     export_schema(&schema_for!(ContractInfoResponse), &out_dir);
     export_schema(&schema_for!(AdminResponse), &out_dir);
+    export_schema(&schema_for!(BalanceResponse), &out_dir);
+    export_schema(&schema_for!(HoldersResponse), &out_dir);
+    export_schema(&schema_for!(HooksResponse), &out_dir);
     export_schema_with_title(
-        &schema_for!(NftInfoResponse),
+        &schema_for!(NftInfoResponse<Extension>),
         &out_dir,
         "NftInfoResponse",
     );
+    export_schema_with_title(
+        &schema_for!(NftsDataPageResponse<Extension>),
+        &out_dir,
+        "NftsDataPageResponse",
+    );
+    export_schema_with_title(
+        &schema_for!(NftsDataResponse<Extension>),
+        &out_dir,
+        "NftsDataResponse",
+    );
     export_schema(&schema_for!(NumTokensResponse), &out_dir);
+    export_schema(&schema_for!(OperatorGrantsResponse), &out_dir);
     export_schema(&schema_for!(OwnerOfResponse), &out_dir);
+    export_schema(&schema_for!(PlanetHookMsg), &out_dir);
+    export_schema(&schema_for!(PlanetLocationResponse), &out_dir);
+    export_schema(&schema_for!(RecentEditsResponse), &out_dir);
+    export_schema_with_title(
+        &schema_for!(SystemPlanetsResponse<Extension>),
+        &out_dir,
+        "SystemPlanetsResponse",
+    );
+    export_schema(&schema_for!(SalePhasesResponse), &out_dir);
+    export_schema(&schema_for!(SpenderApprovalsResponse), &out_dir);
     export_schema(&schema_for!(TokensResponse), &out_dir);
+    export_schema(&schema_for!(WhitelistStatusResponse), &out_dir);
 }
@@ -1,16 +1,33 @@
 #![cfg(test)]
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_binary, to_binary, CosmosMsg, DepsMut, Empty, Response, WasmMsg, Uint128, Coin, attr, Timestamp};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Addr, Binary, ContractResult, CosmosMsg, Deps, DepsMut,
+    Empty, Order, OwnedDeps, Querier, QuerierResult, QueryRequest, Reply, ReplyOn, Response,
+    StdError, SystemResult, WasmMsg, WasmQuery, Uint128, Coin, attr, Timestamp, Storage,
+};
 
 use cw721::{
-    ApprovedForAllResponse, ContractInfoResponse, Cw721Query, Cw721ReceiveMsg, Expiration,
-    NftInfoResponse, OwnerOfResponse, NftDataExtension,
+    AllNftInfoResponse, ApprovedForAllResponse, ContractInfoResponse, Cw721Query,
+    Cw721ReceiveMsg, Expiration, NftInfoResponse, NumTokensResponse, OwnerOfResponse,
+    NftDataExtension, PlanetEvent, PlanetHookMsg, TokensResponse, Trait,
 };
+use k256::ecdsa::signature::Signer;
+use k256::ecdsa::{Signature, SigningKey};
 use terraswap::asset::{Asset, AssetInfo};
 
 use crate::{
-    ContractError, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg, MintMsg, QueryMsg,
+    BalanceResponse, ContractError, Cw721Contract, Cw721DataContract, DataFormat, ExecuteMsg, Extension, Holder,
+    HoldersResponse, HooksResponse, InstantiateMsg, MigrateMsg, MintMsg, NftsDataPageResponse,
+    NftsDataResponse, OperatorGrantsResponse, PhaseWhitelist, QueryMsg, PlanetLocationResponse,
+    RecentEdit, RecentEditsResponse, SalePhase, SalePhaseStatus, SalePhasesResponse,
+    SpenderApproval, SpenderApprovalsResponse, SystemPlanetsResponse, UpdateMsg,
+    WhitelistStatusResponse, WhitelistVoucher,
 };
+use crate::merkle::{self, MerkleTree};
+use crate::voucher;
+use crate::state::{TokenInfo, LEGACY_STORAGE_KEYS, MAX_HOOKS, STORAGE_LAYOUT};
 
 
 
@@ -30,11 +47,14 @@ fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty>
         whitelist_mint_time: 0,
         open_mint_time: 0,
         merkle_root: String::from(MERKLE_ROOT),
+        whitelist_signer: None,
         denom: String::from("uusd"),
         price: 25000000u64,
         max_issuance: 5000,
         token_uri: String::from("ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN"),
-
+        random_mint: None,
+        website_url: None,
+        sale_phases: None,
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -52,10 +72,14 @@ fn setup_contract_whitelist(deps: DepsMut<'_>) -> Cw721Contract<'static, Extensi
         whitelist_mint_time: 1638317032,
         open_mint_time: 1640045032,
         merkle_root: String::from(MERKLE_ROOT),
+        whitelist_signer: None,
         denom: String::from("uusd"),
         price: 25000000u64,
         max_issuance: 5000,
         token_uri: String::from("ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN"),
+        random_mint: None,
+        website_url: None,
+        sale_phases: None,
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -63,6 +87,53 @@ fn setup_contract_whitelist(deps: DepsMut<'_>) -> Cw721Contract<'static, Extensi
     contract
 }
 
+/// Proof of terra1v6qcfytvpcvxmtnyc5arte45jr6gg9nh3vd7y4 for MERKLE_ROOT
+fn whitelist_proof() -> Vec<String> {
+    vec![
+        "5ed6270b017666983d08ab8677d5c76760a7693cb45450d1883ab276928e76ab",
+        "b392cee23198374fa1ac11fb776f6718045d31d399452e146400e79e63871b42",
+        "d7e8b0eece23f548702d8a8622877f24fbf617d0a236f7a3511bd6ec1c09f1b2",
+        "695b6698656be3f8d4c670fd4cd1c2a8ff74a185d152d507aad6dfc10fdc4f06",
+        "e5c1cd4f957e7c3e2faa331b98179b8371c5414d5040d382665c4d29eb316825",
+        "2e08ef89ea5f140d24102f2d3602e8bbea1d943087f5751a06d25b605fdc6b50",
+        "4b2daf8d3c34318bda83fc40e53ddcd9662a8d334c66c7796dad47183885987e",
+        "d01015cb8806d186bd59d4f2f7fd59364362adceb3d3bbfd1a581e2af42d1123",
+        "7157874ca56be03beb6f004e57630439916b6b3c1998e554e8cac60a0e1bc362",
+        "640ea319257b8e0c5667b34b7cddb5f2e3cc0605814fa4c2947087176a25a3cd",
+        "e3fc12e2d33aa131d81835975d0d2db31070b19dce859d23a0d809befc4a1338",
+        "5740fcbbedb3c20466f5253c83f6219bbcdbac1c01d7de006a9ece7900d3cece",
+        "8aab58f25b4497984a5a061048381a171582b5bf4f36501e7aed757a88082be7",
+        "67cfb1d269dcaa4eec59907d9d3238ddfe4c1727deedd5e55b3246aa2aaad3de",
+        "aa4f2f44231e146967f3ebb811ea830aa1415feccb39926d3f3d9d7f3731264a",
+        "41b693c46ddd3bf3c317de946acf1692ed8d85306612f8c57ef91d9dc3bee485",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// Format trait followed by the galaxy location of planet #1
+fn planet_one_traits(format: &str) -> Vec<Trait> {
+    let number = |trait_type: &str, value: &str| Trait {
+        display_type: Some("number".to_string()),
+        trait_type: trait_type.to_string(),
+        value: value.to_string(),
+    };
+    let text = |trait_type: &str, value: &str| Trait {
+        display_type: None,
+        trait_type: trait_type.to_string(),
+        value: value.to_string(),
+    };
+    vec![
+        text("Format", format),
+        number("Arm", "0"),
+        number("System Index", "1"),
+        text("System", "Chunky Lunatic System"),
+        number("Position", "0"),
+        text("Star Color", "Blue"),
+    ]
+}
+
 #[test]
 fn proper_instantiation() {
     let mut deps = mock_dependencies(&[]);
@@ -74,10 +145,14 @@ fn proper_instantiation() {
         whitelist_mint_time: 0,
         open_mint_time: 0,
         merkle_root: String::from(MERKLE_ROOT),
+        whitelist_signer: None,
         denom: String::from("uusd"),
         price: 250000000u64,
         max_issuance: 5000,
         token_uri: String::from("ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN"),
+        random_mint: None,
+        website_url: None,
+        sale_phases: None,
     };
     let info = mock_info("creator", &[]);
 
@@ -127,6 +202,8 @@ fn open_minting_correct_price() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
     let info = mock_info(ADMIN, &[Coin {
@@ -151,11 +228,81 @@ fn open_minting_correct_price() {
             extension: NftDataExtension {
                 image: Some(token_uri.clone()),
                 name: "Planet #1".to_string(),
+                description: None,
+                external_url: None,
+                animation_url: None,
+                attributes: planet_one_traits("Unedited"),
+                custom: None,
             },
         }
     );
 }
 
+#[test]
+fn random_minting_issues_every_id_once() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = Cw721Contract::<Extension, Empty>::default();
+
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        admin: String::from(ADMIN),
+        whitelist_mint_time: 0,
+        open_mint_time: 0,
+        merkle_root: String::from(MERKLE_ROOT),
+        whitelist_signer: None,
+        denom: String::from("uusd"),
+        price: 25000000u64,
+        max_issuance: 25,
+        token_uri: String::from("ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN"),
+        random_mint: Some(true),
+        website_url: None,
+        sale_phases: None,
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: Some("stardust".to_string()),
+        extension: None,
+    });
+    let info = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+
+    // every mint lands in the same block, only the pool shrinks between them
+    let mut minted: Vec<u64> = Vec::new();
+    for _ in 0..25 {
+        let res = contract
+            .execute(deps.as_mut(), mock_env(), info.clone(), mint_msg.clone())
+            .unwrap();
+        let token_id = res.attributes.iter().find(|a| a.key == "token_id").unwrap();
+        minted.push(token_id.value.parse().unwrap());
+    }
+    assert_ne!(minted, (1..=25).collect::<Vec<u64>>());
+    minted.sort_unstable();
+    assert_eq!(minted, (1..=25).collect::<Vec<u64>>());
+
+    // the pool is exhausted
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), info, mint_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MaxIssued {});
+    let count = contract.num_tokens(deps.as_ref()).unwrap();
+    assert_eq!(25, count.count);
+}
+
 #[test]
 fn open_minting_low_price() {
     let env = mock_env();
@@ -174,6 +321,8 @@ fn open_minting_low_price() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
     let info = mock_info(ADMIN, &[Coin {
@@ -203,6 +352,8 @@ fn open_minting_high_price() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
     let info = mock_info(ADMIN, &[Coin {
@@ -232,6 +383,8 @@ fn open_minting_wrong_denom_correct_price() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
     let info = mock_info(ADMIN, &[Coin {
@@ -261,6 +414,8 @@ fn open_minting_wrong_denom_incorrect_price() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
     let info = mock_info(ADMIN, &[Coin {
@@ -285,22 +440,7 @@ fn whitelist_mint_correct_price() {
     let token_uri = "ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN".to_string();
 
     let contract = setup_contract_whitelist(deps.as_mut());
-    let proof = vec!["5ed6270b017666983d08ab8677d5c76760a7693cb45450d1883ab276928e76ab".to_string(),
-    "b392cee23198374fa1ac11fb776f6718045d31d399452e146400e79e63871b42".to_string(),
-    "d7e8b0eece23f548702d8a8622877f24fbf617d0a236f7a3511bd6ec1c09f1b2".to_string(),
-    "695b6698656be3f8d4c670fd4cd1c2a8ff74a185d152d507aad6dfc10fdc4f06".to_string(),
-    "e5c1cd4f957e7c3e2faa331b98179b8371c5414d5040d382665c4d29eb316825".to_string(),
-    "2e08ef89ea5f140d24102f2d3602e8bbea1d943087f5751a06d25b605fdc6b50".to_string(),
-    "4b2daf8d3c34318bda83fc40e53ddcd9662a8d334c66c7796dad47183885987e".to_string(),
-    "d01015cb8806d186bd59d4f2f7fd59364362adceb3d3bbfd1a581e2af42d1123".to_string(),
-    "7157874ca56be03beb6f004e57630439916b6b3c1998e554e8cac60a0e1bc362".to_string(),
-    "640ea319257b8e0c5667b34b7cddb5f2e3cc0605814fa4c2947087176a25a3cd".to_string(),
-    "e3fc12e2d33aa131d81835975d0d2db31070b19dce859d23a0d809befc4a1338".to_string(),
-    "5740fcbbedb3c20466f5253c83f6219bbcdbac1c01d7de006a9ece7900d3cece".to_string(),
-    "8aab58f25b4497984a5a061048381a171582b5bf4f36501e7aed757a88082be7".to_string(),
-    "67cfb1d269dcaa4eec59907d9d3238ddfe4c1727deedd5e55b3246aa2aaad3de".to_string(),
-    "aa4f2f44231e146967f3ebb811ea830aa1415feccb39926d3f3d9d7f3731264a".to_string(),
-    "41b693c46ddd3bf3c317de946acf1692ed8d85306612f8c57ef91d9dc3bee485".to_string()];
+    let proof = whitelist_proof();
 
     let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
         offer_asset: Asset {
@@ -310,6 +450,8 @@ fn whitelist_mint_correct_price() {
             amount: offer_amount,
         },
         proof: Some(proof.clone()),
+        voucher: None,
+        salt: None,
         extension: None,
     });
     let info = mock_info("terra1v6qcfytvpcvxmtnyc5arte45jr6gg9nh3vd7y4", &[Coin {
@@ -334,6 +476,11 @@ fn whitelist_mint_correct_price() {
             extension: NftDataExtension {
                 image: Some(token_uri.clone()),
                 name: "Planet #1".to_string(),
+                description: None,
+                external_url: None,
+                animation_url: None,
+                attributes: planet_one_traits("Unedited"),
+                custom: None,
             },
         }
     );
@@ -347,6 +494,8 @@ fn whitelist_mint_correct_price() {
             amount: offer_amount,
         },
         proof: Some(proof.clone()),
+        voucher: None,
+        salt: None,
         extension: None,
     });
 
@@ -370,22 +519,7 @@ fn whitelist_mint_bad_proof() {
         amount: offer_amount,
     }]);
     let contract = setup_contract_whitelist(deps.as_mut());
-    let proof = vec!["5ed6270b017666983d08ab8677d5c76760a7693cb45450d1883ab276928e76ab".to_string(),
-    "b392cee23198374fa1ac11fb776f6718045d31d399452e146400e79e63871b42".to_string(),
-    "d7e8b0eece23f548702d8a8622877f24fbf617d0a236f7a3511bd6ec1c09f1b2".to_string(),
-    "695b6698656be3f8d4c670fd4cd1c2a8ff74a185d152d507aad6dfc10fdc4f06".to_string(),
-    "e5c1cd4f957e7c3e2faa331b98179b8371c5414d5040d382665c4d29eb316825".to_string(),
-    "2e08ef89ea5f140d24102f2d3602e8bbea1d943087f5751a06d25b605fdc6b50".to_string(),
-    "4b2daf8d3c34318bda83fc40e53ddcd9662a8d334c66c7796dad47183885987e".to_string(),
-    "d01015cb8806d186bd59d4f2f7fd59364362adceb3d3bbfd1a581e2af42d1123".to_string(),
-    "7157874ca56be03beb6f004e57630439916b6b3c1998e554e8cac60a0e1bc362".to_string(),
-    "640ea319257b8e0c5667b34b7cddb5f2e3cc0605814fa4c2947087176a25a3cd".to_string(),
-    "e3fc12e2d33aa131d81835975d0d2db31070b19dce859d23a0d809befc4a1338".to_string(),
-    "5740fcbbedb3c20466f5253c83f6219bbcdbac1c01d7de006a9ece7900d3cece".to_string(),
-    "8aab58f25b4497984a5a061048381a171582b5bf4f36501e7aed757a88082be7".to_string(),
-    "67cfb1d269dcaa4eec59907d9d3238ddfe4c1727deedd5e55b3246aa2aaad3de".to_string(),
-    "aa4f2f44231e146967f3ebb811ea830aa1415feccb39926d3f3d9d7f3731264a".to_string(),
-    "41b693c46ddd3bf3c317de946acf1692ed8d85306612f8c57ef91d9dc3bee485".to_string()];
+    let proof = whitelist_proof();
     let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
         offer_asset: Asset {
             info: AssetInfo::NativeToken {
@@ -394,6 +528,8 @@ fn whitelist_mint_bad_proof() {
             amount: offer_amount,
         },
         proof: Some(proof),
+        voucher: None,
+        salt: None,
         extension: None,
     });
     
@@ -416,22 +552,7 @@ fn whitelist_mint_incorrect_price() {
         amount: offer_amount,
     }]);
     let contract = setup_contract_whitelist(deps.as_mut());
-    let proof = vec!["5ed6270b017666983d08ab8677d5c76760a7693cb45450d1883ab276928e76ab".to_string(),
-    "b392cee23198374fa1ac11fb776f6718045d31d399452e146400e79e63871b42".to_string(),
-    "d7e8b0eece23f548702d8a8622877f24fbf617d0a236f7a3511bd6ec1c09f1b2".to_string(),
-    "695b6698656be3f8d4c670fd4cd1c2a8ff74a185d152d507aad6dfc10fdc4f06".to_string(),
-    "e5c1cd4f957e7c3e2faa331b98179b8371c5414d5040d382665c4d29eb316825".to_string(),
-    "2e08ef89ea5f140d24102f2d3602e8bbea1d943087f5751a06d25b605fdc6b50".to_string(),
-    "4b2daf8d3c34318bda83fc40e53ddcd9662a8d334c66c7796dad47183885987e".to_string(),
-    "d01015cb8806d186bd59d4f2f7fd59364362adceb3d3bbfd1a581e2af42d1123".to_string(),
-    "7157874ca56be03beb6f004e57630439916b6b3c1998e554e8cac60a0e1bc362".to_string(),
-    "640ea319257b8e0c5667b34b7cddb5f2e3cc0605814fa4c2947087176a25a3cd".to_string(),
-    "e3fc12e2d33aa131d81835975d0d2db31070b19dce859d23a0d809befc4a1338".to_string(),
-    "5740fcbbedb3c20466f5253c83f6219bbcdbac1c01d7de006a9ece7900d3cece".to_string(),
-    "8aab58f25b4497984a5a061048381a171582b5bf4f36501e7aed757a88082be7".to_string(),
-    "67cfb1d269dcaa4eec59907d9d3238ddfe4c1727deedd5e55b3246aa2aaad3de".to_string(),
-    "aa4f2f44231e146967f3ebb811ea830aa1415feccb39926d3f3d9d7f3731264a".to_string(),
-    "41b693c46ddd3bf3c317de946acf1692ed8d85306612f8c57ef91d9dc3bee485".to_string()];
+    let proof = whitelist_proof();
     let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
         offer_asset: Asset {
             info: AssetInfo::NativeToken {
@@ -440,6 +561,8 @@ fn whitelist_mint_incorrect_price() {
             amount: offer_amount,
         },
         proof: Some(proof),
+        voucher: None,
+        salt: None,
         extension: None,
     });
     
@@ -462,22 +585,7 @@ fn before_whitelist_mint_correct_price() {
         amount: offer_amount,
     }]);
     let contract = setup_contract_whitelist(deps.as_mut());
-    let proof = vec!["5ed6270b017666983d08ab8677d5c76760a7693cb45450d1883ab276928e76ab".to_string(),
-    "b392cee23198374fa1ac11fb776f6718045d31d399452e146400e79e63871b42".to_string(),
-    "d7e8b0eece23f548702d8a8622877f24fbf617d0a236f7a3511bd6ec1c09f1b2".to_string(),
-    "695b6698656be3f8d4c670fd4cd1c2a8ff74a185d152d507aad6dfc10fdc4f06".to_string(),
-    "e5c1cd4f957e7c3e2faa331b98179b8371c5414d5040d382665c4d29eb316825".to_string(),
-    "2e08ef89ea5f140d24102f2d3602e8bbea1d943087f5751a06d25b605fdc6b50".to_string(),
-    "4b2daf8d3c34318bda83fc40e53ddcd9662a8d334c66c7796dad47183885987e".to_string(),
-    "d01015cb8806d186bd59d4f2f7fd59364362adceb3d3bbfd1a581e2af42d1123".to_string(),
-    "7157874ca56be03beb6f004e57630439916b6b3c1998e554e8cac60a0e1bc362".to_string(),
-    "640ea319257b8e0c5667b34b7cddb5f2e3cc0605814fa4c2947087176a25a3cd".to_string(),
-    "e3fc12e2d33aa131d81835975d0d2db31070b19dce859d23a0d809befc4a1338".to_string(),
-    "5740fcbbedb3c20466f5253c83f6219bbcdbac1c01d7de006a9ece7900d3cece".to_string(),
-    "8aab58f25b4497984a5a061048381a171582b5bf4f36501e7aed757a88082be7".to_string(),
-    "67cfb1d269dcaa4eec59907d9d3238ddfe4c1727deedd5e55b3246aa2aaad3de".to_string(),
-    "aa4f2f44231e146967f3ebb811ea830aa1415feccb39926d3f3d9d7f3731264a".to_string(),
-    "41b693c46ddd3bf3c317de946acf1692ed8d85306612f8c57ef91d9dc3bee485".to_string()];
+    let proof = whitelist_proof();
     let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
         offer_asset: Asset {
             info: AssetInfo::NativeToken {
@@ -486,6 +594,8 @@ fn before_whitelist_mint_correct_price() {
             amount: offer_amount,
         },
         proof: Some(proof),
+        voucher: None,
+        salt: None,
         extension: None,
     });
     assert_eq!(env.block.time, Timestamp::from_seconds(1634256232));
@@ -517,6 +627,8 @@ fn approving_all_revoking_all() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
 
@@ -536,6 +648,8 @@ fn approving_all_revoking_all() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
 
@@ -736,6 +850,8 @@ fn transferring_nft() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
 
@@ -800,10 +916,12 @@ fn sending_nft() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
 
-    let minter = mock_info(&"venus".to_string(), &[Coin {
+    let minter = mock_info("venus", &[Coin {
         denom: "uusd".to_string(),
         amount: offer_amount,
     }]);
@@ -876,6 +994,8 @@ fn approving_revoking() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
 
@@ -1002,6 +1122,8 @@ fn query_tokens_by_owner() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
     contract
@@ -1016,6 +1138,8 @@ fn query_tokens_by_owner() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
     contract
@@ -1030,6 +1154,8 @@ fn query_tokens_by_owner() {
             amount: offer_amount,
         },
         proof: None,
+        voucher: None,
+        salt: None,
         extension: None,
     });
     contract
@@ -1063,3 +1189,2258 @@ fn query_tokens_by_owner() {
     assert_eq!(&by_demeter[1..], &tokens.tokens[..]);
 }
 
+
+#[test]
+fn setting_planet_profile() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+    let token_uri = "ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN".to_string();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let minter = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    contract.execute(deps.as_mut(), mock_env(), minter, mint_msg).unwrap();
+
+    // random cannot name the planet
+    let profile_msg = ExecuteMsg::SetProfile {
+        token_id: "1".to_string(),
+        name: Some("Gaia".to_string()),
+        description: Some("Home of the harvest".to_string()),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("random", &[]), profile_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // names and descriptions are checked
+    let owner = mock_info("demeter", &[]);
+    let bad_name = ExecuteMsg::SetProfile {
+        token_id: "1".to_string(),
+        name: Some("<b>Gaia</b>".to_string()),
+        description: None,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), bad_name)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidName {});
+    let bad_description = ExecuteMsg::SetProfile {
+        token_id: "1".to_string(),
+        name: None,
+        description: Some("x".repeat(257)),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), bad_description)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidDescription {});
+
+    // owner can
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), profile_msg)
+        .unwrap();
+    let info = contract.nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(
+        info.extension,
+        NftDataExtension {
+            image: Some(token_uri.clone()),
+            name: "Gaia".to_string(),
+            description: Some("Home of the harvest".to_string()),
+            external_url: None,
+            animation_url: None,
+            attributes: planet_one_traits("Unedited"),
+            custom: None,
+        }
+    );
+    let all_info = contract
+        .all_nft_info(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(all_info.info, info);
+
+    // clearing the profile falls back to the default name
+    let clear_msg = ExecuteMsg::SetProfile {
+        token_id: "1".to_string(),
+        name: None,
+        description: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), clear_msg)
+        .unwrap();
+    let default_extension = NftDataExtension {
+        image: Some(token_uri),
+        name: "Planet #1".to_string(),
+        description: None,
+        external_url: None,
+        animation_url: None,
+        attributes: planet_one_traits("Unedited"),
+        custom: None,
+    };
+    let info = contract.nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(info.extension, default_extension);
+
+    // the profile stays with the planet when it changes owner
+    let profile_msg = ExecuteMsg::SetProfile {
+        token_id: "1".to_string(),
+        name: Some("Gaia".to_string()),
+        description: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner.clone(), profile_msg)
+        .unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "ceres".to_string(),
+        token_id: "1".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), owner, transfer_msg)
+        .unwrap();
+    let info = contract.nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(info.extension, NftDataExtension {
+        name: "Gaia".to_string(),
+        ..default_extension
+    });
+}
+
+#[test]
+fn custom_extension_in_info_responses() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = Cw721Contract::<Option<String>, Empty>::default();
+
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        admin: String::from(ADMIN),
+        whitelist_mint_time: 0,
+        open_mint_time: 0,
+        merkle_root: String::from(MERKLE_ROOT),
+        whitelist_signer: None,
+        denom: String::from("uusd"),
+        price: 25000000u64,
+        max_issuance: 5000,
+        token_uri: String::from("ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN"),
+        random_mint: None,
+        website_url: None,
+        sale_phases: None,
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Option<String>> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: Some("ringed gas giant".to_string()),
+    });
+    let minter = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    contract.execute(deps.as_mut(), mock_env(), minter, mint_msg).unwrap();
+
+    // the stored extension is returned next to the planet metadata
+    let info = contract.nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(info.extension.name, "Planet #1".to_string());
+    assert_eq!(info.extension.custom, Some("ringed gas giant".to_string()));
+
+    let all_info = contract
+        .all_nft_info(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(all_info.info, info);
+    assert_eq!(all_info.access.owner, "demeter".to_string());
+}
+
+#[test]
+fn planet_metadata_links_and_traits() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let minter = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    contract.execute(deps.as_mut(), mock_env(), minter, mint_msg).unwrap();
+
+    // only the admin can link the website
+    let website_msg = ExecuteMsg::UpdateWebsiteUrl {
+        website_url: Some("https://planets.example/".to_string()),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), website_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), website_msg)
+        .unwrap();
+
+    // editing the planet changes its format trait
+    let update_msg = ExecuteMsg::Update(UpdateMsg {
+        token_id: "1".to_string(),
+        data: Some("VOX1=//8=A=SzgngmxZ".to_string()),
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), update_msg)
+        .unwrap();
+
+    let info = contract.nft_info(deps.as_ref(), "1".to_string()).unwrap();
+    assert_eq!(
+        info.extension.external_url,
+        Some("https://planets.example/planet/1".to_string())
+    );
+    assert_eq!(
+        info.extension.animation_url,
+        Some("https://planets.example/explore/1".to_string())
+    );
+    assert_eq!(info.extension.attributes, planet_one_traits("Voxel"));
+
+    // unsetting the website removes the links
+    let website_msg = ExecuteMsg::UpdateWebsiteUrl { website_url: None };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), website_msg)
+        .unwrap();
+    let all_info = contract
+        .all_nft_info(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(all_info.info.extension.external_url, None);
+    assert_eq!(all_info.info.extension.animation_url, None);
+}
+
+#[test]
+fn query_planet_location() {
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    // locations are known before the planets are minted
+    let query_msg = QueryMsg::PlanetLocation {
+        token_id: "12".to_string(),
+    };
+    let res: PlanetLocationResponse = from_binary(
+        &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        PlanetLocationResponse {
+            token_id: "12".to_string(),
+            arm: 2,
+            system_index: 3,
+            system_name: "Spicy Spectrum System".to_string(),
+            position: 4,
+            star_color: "Blue".to_string(),
+        }
+    );
+
+    // the last planet sits alone in the last system
+    let res = contract.planet_location(deps.as_ref(), "5000".to_string()).unwrap();
+    assert_eq!(
+        res,
+        PlanetLocationResponse {
+            token_id: "5000".to_string(),
+            arm: 2,
+            system_index: 1251,
+            system_name: "Toxic Turtle System".to_string(),
+            position: 0,
+            star_color: "Orange".to_string(),
+        }
+    );
+    let res = contract.planet_location(deps.as_ref(), "2500".to_string()).unwrap();
+    assert_eq!(res.system_index, 626);
+    assert_eq!(res.system_name, "Ergonomic Mars System".to_string());
+    assert_eq!(res.star_color, "White".to_string());
+
+    // ids outside of the issuance are rejected
+    contract
+        .planet_location(deps.as_ref(), "0".to_string())
+        .unwrap_err();
+    contract
+        .planet_location(deps.as_ref(), "5001".to_string())
+        .unwrap_err();
+    contract
+        .planet_location(deps.as_ref(), "planet".to_string())
+        .unwrap_err();
+}
+
+#[test]
+fn query_system_planets() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let minter = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    for _ in 0..5 {
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg.clone())
+            .unwrap();
+    }
+
+    // the second system holds planets 4 to 7, only 4 and 5 are minted
+    let query_msg = QueryMsg::SystemPlanets { system_index: 2 };
+    let res: SystemPlanetsResponse<Extension> = from_binary(
+        &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.system_index, 2);
+    assert_eq!(res.token_ids, vec!["4", "5", "6", "7"]);
+    let minted: Vec<_> = res.nfts.iter().map(|nft| nft.token_id.clone().unwrap()).collect();
+    assert_eq!(minted, vec!["4", "5"]);
+    assert_eq!(res.nfts[0].access.owner, "demeter".to_string());
+    assert_eq!(res.nfts[0].extension.name, "Planet #4".to_string());
+
+    // the last system is cut at the maximum issuance
+    let res = contract.system_planets(deps.as_ref(), mock_env(), 1251).unwrap();
+    assert_eq!(res.token_ids, vec!["5000"]);
+    assert_eq!(res.nfts.len(), 0);
+
+    contract
+        .system_planets(deps.as_ref(), mock_env(), 0)
+        .unwrap_err();
+    contract
+        .system_planets(deps.as_ref(), mock_env(), 1252)
+        .unwrap_err();
+}
+
+#[test]
+fn query_recent_edits() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    for owner in ["demeter", "ceres"] {
+        let minter = mock_info(owner, &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }]);
+        contract
+            .execute(deps.as_mut(), mock_env(), minter, mint_msg.clone())
+            .unwrap();
+    }
+
+    // alternate edits between the two planets, one block apart
+    let mut env = mock_env();
+    for i in 0..105u64 {
+        let (owner, token_id) = if i % 2 == 0 { ("demeter", "1") } else { ("ceres", "2") };
+        env.block.time = Timestamp::from_seconds(1640045032 + i);
+        let update_msg = ExecuteMsg::Update(UpdateMsg {
+            token_id: token_id.to_string(),
+            data: Some(format!("GEO1={}", i)),
+        });
+        contract
+            .execute(deps.as_mut(), env.clone(), mock_info(owner, &[]), update_msg)
+            .unwrap();
+    }
+
+    // newest first
+    let query_msg = QueryMsg::RecentEdits {
+        start_after: None,
+        limit: Some(2),
+    };
+    let res: RecentEditsResponse = from_binary(
+        &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.edits,
+        vec![
+            RecentEdit {
+                edit_id: 105,
+                token_id: "1".to_string(),
+                editor: "demeter".to_string(),
+                time: Timestamp::from_seconds(1640045136),
+            },
+            RecentEdit {
+                edit_id: 104,
+                token_id: "2".to_string(),
+                editor: "ceres".to_string(),
+                time: Timestamp::from_seconds(1640045135),
+            },
+        ]
+    );
+
+    // paginate down to the oldest edit still kept
+    let mut start_after = None;
+    let mut edit_ids = vec![];
+    loop {
+        let res = contract.recent_edits(deps.as_ref(), start_after, None).unwrap();
+        if res.edits.is_empty() {
+            break;
+        }
+        start_after = res.edits.last().map(|edit| edit.edit_id);
+        edit_ids.extend(res.edits.iter().map(|edit| edit.edit_id));
+    }
+    assert_eq!(edit_ids, (6..=105).rev().collect::<Vec<u64>>());
+}
+
+#[test]
+fn query_tokens_by_format() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let minter = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    for _ in 0..5 {
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg.clone())
+            .unwrap();
+    }
+
+    let update = |deps: DepsMut, token_id: &str, data: Option<&str>| {
+        let update_msg = ExecuteMsg::Update(UpdateMsg {
+            token_id: token_id.to_string(),
+            data: data.map(String::from),
+        });
+        contract
+            .execute(deps, mock_env(), mock_info("demeter", &[]), update_msg)
+            .unwrap();
+    };
+    update(deps.as_mut(), "1", Some("GEO1=AAAA"));
+    update(deps.as_mut(), "2", Some("VOX1=AAAA"));
+    update(deps.as_mut(), "3", Some("GEO1=BBBB"));
+    update(deps.as_mut(), "4", Some("PLANET"));
+    // moving between formats keeps the index in step
+    update(deps.as_mut(), "4", Some("GEO1=CCCC"));
+    update(deps.as_mut(), "2", None);
+
+    let tokens_by = |deps: Deps, format: DataFormat| {
+        contract
+            .tokens_by_format(deps, format, None, None)
+            .unwrap()
+            .tokens
+    };
+    assert_eq!(tokens_by(deps.as_ref(), DataFormat::Geo1), vec!["1", "3", "4"]);
+    assert_eq!(tokens_by(deps.as_ref(), DataFormat::None), vec!["2", "5"]);
+    assert!(tokens_by(deps.as_ref(), DataFormat::Vox1).is_empty());
+    assert!(tokens_by(deps.as_ref(), DataFormat::Other).is_empty());
+
+    // paginate
+    let query_msg = QueryMsg::TokensByFormat {
+        format: DataFormat::Geo1,
+        start_after: Some("1".to_string()),
+        limit: Some(1),
+    };
+    let res: TokensResponse = from_binary(
+        &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.tokens, vec!["3"]);
+
+    let count_of = |deps: Deps, format: DataFormat| {
+        let query_msg = QueryMsg::NumTokensByFormat { format };
+        let res: NumTokensResponse = from_binary(
+            &contract.query(deps, mock_env(), query_msg).unwrap(),
+        )
+        .unwrap();
+        res.count
+    };
+    assert_eq!(count_of(deps.as_ref(), DataFormat::Geo1), 3);
+    assert_eq!(count_of(deps.as_ref(), DataFormat::None), 2);
+    assert_eq!(count_of(deps.as_ref(), DataFormat::Vox1), 0);
+    assert_eq!(count_of(deps.as_ref(), DataFormat::Other), 0);
+}
+
+#[test]
+fn query_balances_and_holders() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    for owner in ["demeter", "demeter", "demeter", "ceres"] {
+        let minter = mock_info(owner, &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }]);
+        contract
+            .execute(deps.as_mut(), mock_env(), minter, mint_msg.clone())
+            .unwrap();
+    }
+
+    // ceres gives its only planet to hermes, demeter gives one to ceres
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "hermes".to_string(),
+        token_id: "4".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("ceres", &[]), transfer_msg)
+        .unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "ceres".to_string(),
+        token_id: "1".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), transfer_msg)
+        .unwrap();
+
+    let balance_of = |owner: &str| {
+        let query_msg = QueryMsg::Balance {
+            owner: owner.to_string(),
+        };
+        let res: BalanceResponse = from_binary(
+            &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+        )
+        .unwrap();
+        res.balance
+    };
+    assert_eq!(balance_of("demeter"), 2);
+    assert_eq!(balance_of("ceres"), 1);
+    assert_eq!(balance_of("hermes"), 1);
+    assert_eq!(balance_of("zeus"), 0);
+
+    let holder = |address: &str, tokens: u64| Holder {
+        address: address.to_string(),
+        tokens,
+    };
+    let query_msg = QueryMsg::Holders {
+        start_after: None,
+        limit: None,
+    };
+    let res: HoldersResponse = from_binary(
+        &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+    )
+    .unwrap();
+    let all_holders = vec![holder("ceres", 1), holder("demeter", 2), holder("hermes", 1)];
+    assert_eq!(res.holders, all_holders);
+
+    // paginate
+    let res = contract
+        .holders(deps.as_ref(), Some("ceres".to_string()), Some(1))
+        .unwrap();
+    assert_eq!(res.holders, vec![holder("demeter", 2)]);
+}
+
+#[test]
+fn query_nfts_data_pages() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    for owner in ["demeter", "ceres", "demeter", "ceres", "demeter"] {
+        let minter = mock_info(owner, &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }]);
+        contract
+            .execute(deps.as_mut(), mock_env(), minter, mint_msg.clone())
+            .unwrap();
+    }
+    let update_msg = ExecuteMsg::Update(UpdateMsg {
+        token_id: "3".to_string(),
+        data: Some("VOX1=AAAA".to_string()),
+    });
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), update_msg)
+        .unwrap();
+
+    // the answer keeps the requested order, ids that are not minted are reported
+    let query_msg = QueryMsg::NftsData {
+        token_ids: vec![
+            "3".to_string(),
+            "10".to_string(),
+            "2".to_string(),
+            "9".to_string(),
+            "3".to_string(),
+        ],
+    };
+    let res: NftsDataResponse<Extension> = from_binary(
+        &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+    )
+    .unwrap();
+    let ids: Vec<_> = res
+        .nfts
+        .unwrap()
+        .into_iter()
+        .map(|nft| nft.token_id.unwrap())
+        .collect();
+    assert_eq!(ids, vec!["3", "2"]);
+    assert_eq!(res.missing, vec!["10", "9"]);
+
+    // page through the whole collection
+    let query_msg = QueryMsg::AllNftsData {
+        start_after: None,
+        limit: Some(3),
+    };
+    let res: NftsDataPageResponse<Extension> = from_binary(
+        &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+    )
+    .unwrap();
+    let ids: Vec<_> = res.nfts.iter().map(|nft| nft.token_id.clone().unwrap()).collect();
+    assert_eq!(ids, vec!["1", "2", "3"]);
+    assert_eq!(res.nfts[2].data, Some("VOX1=AAAA".to_string()));
+    assert_eq!(res.nfts[1].access.owner, "ceres");
+    assert_eq!(res.next_start_after, Some("3".to_string()));
+
+    let res = contract
+        .all_nfts_data(deps.as_ref(), mock_env(), res.next_start_after, Some(3))
+        .unwrap();
+    let ids: Vec<_> = res.nfts.iter().map(|nft| nft.token_id.clone().unwrap()).collect();
+    assert_eq!(ids, vec!["4", "5"]);
+    assert_eq!(res.next_start_after, None);
+
+    // page through the planets of one owner
+    let query_msg = QueryMsg::OwnerNftsData {
+        owner: "demeter".to_string(),
+        start_after: None,
+        limit: Some(2),
+    };
+    let res: NftsDataPageResponse<Extension> = from_binary(
+        &contract.query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+    )
+    .unwrap();
+    let ids: Vec<_> = res.nfts.iter().map(|nft| nft.token_id.clone().unwrap()).collect();
+    assert_eq!(ids, vec!["1", "3"]);
+    assert!(res.nfts.iter().all(|nft| nft.access.owner == "demeter"));
+    assert_eq!(res.next_start_after, Some("3".to_string()));
+
+    let res = contract
+        .owner_nfts_data(
+            deps.as_ref(),
+            mock_env(),
+            "demeter".to_string(),
+            res.next_start_after,
+            Some(2),
+        )
+        .unwrap();
+    let ids: Vec<_> = res.nfts.iter().map(|nft| nft.token_id.clone().unwrap()).collect();
+    assert_eq!(ids, vec!["5"]);
+    assert_eq!(res.next_start_after, None);
+}
+
+#[test]
+fn tokens_enumerate_in_numeric_order() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let minter = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    for _ in 0..12 {
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg.clone())
+            .unwrap();
+    }
+
+    let tokens = contract
+        .all_tokens(deps.as_ref(), None, Some(30))
+        .unwrap()
+        .tokens;
+    let expected: Vec<String> = (1..=12).map(|id| id.to_string()).collect();
+    assert_eq!(tokens, expected);
+
+    let tokens = contract
+        .tokens(deps.as_ref(), "demeter".to_string(), Some("9".to_string()), None)
+        .unwrap()
+        .tokens;
+    assert_eq!(tokens, vec!["10", "11", "12"]);
+
+    // ids are only accepted in their plain decimal form
+    let err = contract
+        .owner_of(deps.as_ref(), mock_env(), "01".to_string(), false)
+        .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Invalid token id: 01"));
+}
+
+#[test]
+fn migrating_moves_tokens_to_numeric_keys() {
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    // tokens as stored before the numeric keys
+    for id in [1, 2, 10, 11] {
+        let owner = if id % 2 == 0 { "demeter" } else { "ceres" };
+        let token = TokenInfo::<Extension> {
+            owner: Addr::unchecked(owner),
+            approvals: vec![],
+            token_uri: None,
+            data: Some(format!("GEO1={}", id)),
+            extension: None,
+        };
+        contract
+            .legacy_tokens
+            .save(deps.as_mut().storage, &id.to_string(), &token)
+            .unwrap();
+    }
+
+    // only the admin moves tokens
+    let migrate_msg = ExecuteMsg::MigrateTokens {
+        start_after: None,
+        limit: Some(3),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("ceres", &[]), migrate_msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // a full batch hands out the cursor to the next one, the ids go in string order
+    let batch = |deps: DepsMut, start_after: Option<String>| {
+        let migrate_msg = ExecuteMsg::MigrateTokens {
+            start_after,
+            limit: Some(3),
+        };
+        let res = contract
+            .execute(deps, mock_env(), mock_info(ADMIN, &[]), migrate_msg)
+            .unwrap();
+        (res.attributes[1].value.clone(), res.attributes[2].value.clone())
+    };
+    let (moved, next) = batch(deps.as_mut(), None);
+    assert_eq!((moved.as_str(), next.as_str()), ("3", "11"));
+    let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap().tokens;
+    assert_eq!(tokens, vec!["1", "10", "11"]);
+    let (moved, next) = batch(deps.as_mut(), Some(next));
+    assert_eq!((moved.as_str(), next.as_str()), ("1", ""));
+
+    let tokens = contract.all_tokens(deps.as_ref(), None, None).unwrap().tokens;
+    assert_eq!(tokens, vec!["1", "2", "10", "11"]);
+    let tokens = contract
+        .tokens(deps.as_ref(), "demeter".to_string(), None, None)
+        .unwrap()
+        .tokens;
+    assert_eq!(tokens, vec!["2", "10"]);
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "11".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "ceres");
+    let nfts = contract
+        .all_nfts_data(deps.as_ref(), mock_env(), Some("2".to_string()), Some(1))
+        .unwrap()
+        .nfts;
+    assert_eq!(nfts[0].data, Some("GEO1=10".to_string()));
+    assert_eq!(contract.format_count(&deps.storage, DataFormat::Geo1).unwrap(), 4);
+    assert_eq!(contract.balance(&deps.storage, &Addr::unchecked("ceres")).unwrap(), 2);
+
+    // nothing is left under the old keys
+    let legacy = contract
+        .legacy_tokens
+        .prefix(())
+        .keys(&deps.storage, None, None, Order::Ascending)
+        .count();
+    assert_eq!(legacy, 0);
+}
+
+#[test]
+fn migrating_moves_state_out_of_legacy_namespaces() {
+    let mut deps = mock_dependencies(&[]);
+    let legacy = Cw721Contract::<Extension, Empty>::new(LEGACY_STORAGE_KEYS);
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        admin: String::from(ADMIN),
+        whitelist_mint_time: 100,
+        open_mint_time: 200,
+        merkle_root: String::from(MERKLE_ROOT),
+        whitelist_signer: None,
+        denom: String::from("uusd"),
+        price: 25000000u64,
+        max_issuance: 5000,
+        token_uri: String::from("ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN"),
+        random_mint: None,
+        website_url: None,
+        sale_phases: None,
+    };
+    legacy
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+    // contracts from before the storage layout was tracked
+    legacy.storage_layout.remove(deps.as_mut().storage);
+    legacy.token_count.save(deps.as_mut().storage, &3).unwrap();
+    let (ceres, demeter) = (Addr::unchecked("ceres"), Addr::unchecked("demeter"));
+    legacy
+        .operators
+        .save(deps.as_mut().storage, (&ceres, &demeter), &Expiration::Never {})
+        .unwrap();
+    legacy.legacy_claimed.save(deps.as_mut().storage, &demeter, &true).unwrap();
+
+    // the raw keys show the misplaced state
+    assert_eq!(deps.storage.get(b"merkle_root"), Some(b"5000".to_vec()));
+    assert_eq!(deps.storage.get(b"tokens"), Some(b"\"100000000000\"".to_vec()));
+
+    let contract = Cw721Contract::<Extension, Empty>::default();
+    contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+
+    let storage = &deps.storage;
+    assert_eq!(contract.max_issuance.load(storage).unwrap(), 5000);
+    assert_eq!(contract.token_count.load(storage).unwrap(), 3);
+    assert_eq!(contract.denom.load(storage).unwrap(), "uusd");
+    assert_eq!(contract.price.load(storage).unwrap(), Uint128::from(25000000u128));
+    assert_eq!(contract.merkle_root.load(storage).unwrap(), MERKLE_ROOT);
+    assert_eq!(
+        contract.whitelist_mint_time.load(storage).unwrap(),
+        Timestamp::from_seconds(100)
+    );
+    assert_eq!(
+        contract.open_mint_time.load(storage).unwrap(),
+        Timestamp::from_seconds(200)
+    );
+    assert_eq!(
+        contract.operators.load(storage, (&ceres, &demeter)).unwrap(),
+        Expiration::Never {}
+    );
+    assert_eq!(contract.claimed.load(storage, &demeter).unwrap(), 1);
+    assert_eq!(contract.storage_layout.load(storage).unwrap(), STORAGE_LAYOUT);
+
+    // the old namespaces are empty and hold no stale copies
+    assert_eq!(deps.storage.get(b"tokens"), None);
+    assert_eq!(deps.storage.get(b"operators"), None);
+    let old_operators = legacy
+        .operators
+        .keys(&deps.storage, None, None, Order::Ascending)
+        .count();
+    assert_eq!(old_operators, 0);
+    assert_eq!(legacy.legacy_claimed.may_load(&deps.storage, &demeter).unwrap(), None);
+
+    // migrating again leaves everything in place
+    contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    assert_eq!(contract.max_issuance.load(&deps.storage).unwrap(), 5000);
+    assert_eq!(contract.merkle_root.load(&deps.storage).unwrap(), MERKLE_ROOT);
+}
+
+#[test]
+fn query_whitelist_status() {
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(1639526632);
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract_whitelist(deps.as_mut());
+    let whitelisted = "terra1v6qcfytvpcvxmtnyc5arte45jr6gg9nh3vd7y4";
+
+    let status_of = |deps: Deps, address: &str, proof: Vec<String>| {
+        let query_msg = QueryMsg::WhitelistStatus {
+            address: address.to_string(),
+            proof,
+            voucher: None,
+        };
+        let res: WhitelistStatusResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res
+    };
+
+    let res = status_of(deps.as_ref(), whitelisted, whitelist_proof());
+    assert_eq!(
+        res,
+        WhitelistStatusResponse {
+            phase: None,
+            merkle_root: Some(MERKLE_ROOT.to_string()),
+            valid: true,
+            claimed: false,
+            minted: 0,
+            cap: Some(1),
+            remaining: Some(1),
+        }
+    );
+
+    // the proof of one address does not work for another
+    let res = status_of(deps.as_ref(), "demeter", whitelist_proof());
+    assert_eq!(
+        res,
+        WhitelistStatusResponse {
+            phase: None,
+            merkle_root: Some(MERKLE_ROOT.to_string()),
+            valid: false,
+            claimed: false,
+            minted: 0,
+            cap: Some(1),
+            remaining: Some(0),
+        }
+    );
+
+    // malformed proofs are rejected like in mint
+    let query_msg = QueryMsg::WhitelistStatus {
+        address: whitelisted.to_string(),
+        proof: vec!["zz".to_string()],
+        voucher: None,
+    };
+    let err = contract.query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err(ContractError::InvalidHexProof {}.to_string()));
+
+    // after the whitelist mint nothing is left
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: Some(whitelist_proof()),
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let info = mock_info(whitelisted, &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    contract.execute(deps.as_mut(), env, info, mint_msg).unwrap();
+
+    let res = status_of(deps.as_ref(), whitelisted, whitelist_proof());
+    assert_eq!(
+        res,
+        WhitelistStatusResponse {
+            phase: None,
+            merkle_root: Some(MERKLE_ROOT.to_string()),
+            valid: true,
+            claimed: true,
+            minted: 1,
+            cap: Some(1),
+            remaining: Some(0),
+        }
+    );
+}
+
+#[test]
+fn merkle_tree_proofs_pass_whitelist_mint() {
+    let addresses: Vec<String> = ["ceres", "demeter", "hermes", "hestia", "zeus"]
+        .iter()
+        .map(|address| address.to_string())
+        .collect();
+    let tree = MerkleTree::new(&addresses).unwrap();
+    for (index, address) in addresses.iter().enumerate() {
+        assert!(merkle::verify_proof(&tree.root(), address, &tree.proof(index)).unwrap());
+    }
+    // proofs are bound to their address
+    assert!(!merkle::verify_proof(&tree.root(), "hades", &tree.proof(0)).unwrap());
+    assert!(!merkle::verify_proof(&tree.root(), "ceres", &tree.proof(1)).unwrap());
+    // a single address is its own root
+    let single = MerkleTree::new(&addresses[..1]).unwrap();
+    assert!(single.proof(0).is_empty());
+    assert!(merkle::verify_proof(&single.root(), "ceres", &[]).unwrap());
+    assert!(MerkleTree::new(&[]).is_err());
+
+    let mut deps = mock_dependencies(&[]);
+    let contract = Cw721Contract::<Extension, Empty>::default();
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        admin: String::from(ADMIN),
+        whitelist_mint_time: 1638317032,
+        open_mint_time: 1640045032,
+        merkle_root: tree.root(),
+        whitelist_signer: None,
+        denom: String::from("uusd"),
+        price: 25000000u64,
+        max_issuance: 5000,
+        token_uri: String::from("ipfs://Qmc8nKmttf5EHAiDSy1huoJqnYKVg58dLwmNV8TQHhQnrN"),
+        random_mint: None,
+        website_url: None,
+        sale_phases: None,
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(1639526632);
+    let offer_amount = Uint128::from(25000000u128);
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: Some(tree.proof(4)),
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let info = mock_info("zeus", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    contract.execute(deps.as_mut(), env, info, mint_msg).unwrap();
+
+    let owner = contract
+        .owner_of(deps.as_ref(), mock_env(), "1".to_string(), false)
+        .unwrap();
+    assert_eq!(owner.owner, "zeus");
+}
+
+#[test]
+fn data_helpers_build_contract_messages() {
+    let helper = Cw721DataContract(Addr::unchecked("planets"));
+    let parse = |msg: CosmosMsg| -> (ExecuteMsg<Extension>, Vec<Coin>) {
+        match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) => {
+                assert_eq!(contract_addr, "planets");
+                (from_binary(&msg).unwrap(), funds)
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        }
+    };
+
+    let price = Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(25000000u128),
+    };
+    let mint = helper
+        .mint(price.clone(), Some(vec!["ab".to_string()]), None, None::<Empty>)
+        .unwrap();
+    let (msg, funds) = parse(mint);
+    assert_eq!(funds, vec![price.clone()]);
+    let voucher = WhitelistVoucher {
+        allocation: 2,
+        expires: 1639600000,
+        signature: Binary::from(vec![1; 64]),
+    };
+    let voucher_mint = helper
+        .mint_with_voucher(price.clone(), voucher.clone(), None, None::<Empty>)
+        .unwrap();
+    match parse(voucher_mint).0 {
+        ExecuteMsg::Mint(mint) => assert_eq!(mint.voucher, Some(voucher)),
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    assert_eq!(
+        msg,
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            proof: Some(vec!["ab".to_string()]),
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: price.amount,
+            },
+            voucher: None,
+            salt: None,
+            extension: None,
+        })
+    );
+
+    let expected = vec![
+        (
+            helper.transfer_nft("venus", "1").unwrap(),
+            ExecuteMsg::TransferNft {
+                recipient: "venus".to_string(),
+                token_id: "1".to_string(),
+            },
+        ),
+        (
+            helper.send_nft("market", "1", to_binary("hi").unwrap()).unwrap(),
+            ExecuteMsg::SendNft {
+                contract: "market".to_string(),
+                token_id: "1".to_string(),
+                msg: to_binary("hi").unwrap(),
+            },
+        ),
+        (
+            helper.approve("random", "1", Some(Expiration::AtHeight(10))).unwrap(),
+            ExecuteMsg::Approve {
+                spender: "random".to_string(),
+                token_id: "1".to_string(),
+                expires: Some(Expiration::AtHeight(10)),
+            },
+        ),
+        (
+            helper.revoke("random", "1").unwrap(),
+            ExecuteMsg::Revoke {
+                spender: "random".to_string(),
+                token_id: "1".to_string(),
+            },
+        ),
+        (
+            helper.approve_all("operator", None).unwrap(),
+            ExecuteMsg::ApproveAll {
+                operator: "operator".to_string(),
+                expires: None,
+            },
+        ),
+        (
+            helper.revoke_all("operator").unwrap(),
+            ExecuteMsg::RevokeAll {
+                operator: "operator".to_string(),
+            },
+        ),
+        (
+            helper.update("1", Some("data".to_string())).unwrap(),
+            ExecuteMsg::Update(UpdateMsg {
+                token_id: "1".to_string(),
+                data: Some("data".to_string()),
+            }),
+        ),
+        (
+            helper
+                .set_profile("1", Some("Home".to_string()), None)
+                .unwrap(),
+            ExecuteMsg::SetProfile {
+                token_id: "1".to_string(),
+                name: Some("Home".to_string()),
+                description: None,
+            },
+        ),
+        (
+            helper.withdraw("uusd").unwrap(),
+            ExecuteMsg::Withdraw {
+                denom: "uusd".to_string(),
+            },
+        ),
+        (
+            helper.update_price(30000000).unwrap(),
+            ExecuteMsg::UpdatePrice { price: 30000000 },
+        ),
+        (
+            helper.update_website_url(None).unwrap(),
+            ExecuteMsg::UpdateWebsiteUrl { website_url: None },
+        ),
+        (
+            helper.add_hook("gallery").unwrap(),
+            ExecuteMsg::AddHook {
+                addr: "gallery".to_string(),
+            },
+        ),
+        (
+            helper.remove_hook("gallery").unwrap(),
+            ExecuteMsg::RemoveHook {
+                addr: "gallery".to_string(),
+            },
+        ),
+        (
+            helper.update_whitelist_signer(None).unwrap(),
+            ExecuteMsg::UpdateWhitelistSigner { public_key: None },
+        ),
+        (
+            helper
+                .update_sale_phases(vec![SalePhase {
+                    name: "public".to_string(),
+                    start_time: 1639526632,
+                    end_time: None,
+                    whitelist: PhaseWhitelist::None,
+                    price: 25000000,
+                    wallet_cap: Some(2),
+                    supply_cap: None,
+                }])
+                .unwrap(),
+            ExecuteMsg::UpdateSalePhases {
+                phases: vec![SalePhase {
+                    name: "public".to_string(),
+                    start_time: 1639526632,
+                    end_time: None,
+                    whitelist: PhaseWhitelist::None,
+                    price: 25000000,
+                    wallet_cap: Some(2),
+                    supply_cap: None,
+                }],
+            },
+        ),
+        (
+            helper
+                .prune_expired("venus", Some("mars".to_string()), Some("7".to_string()), Some(10))
+                .unwrap(),
+            ExecuteMsg::PruneExpired {
+                owner: "venus".to_string(),
+                operator_start_after: Some("mars".to_string()),
+                start_after: Some("7".to_string()),
+                limit: Some(10),
+            },
+        ),
+        (
+            helper.migrate_tokens(Some("7".to_string()), Some(10)).unwrap(),
+            ExecuteMsg::MigrateTokens {
+                start_after: Some("7".to_string()),
+                limit: Some(10),
+            },
+        ),
+    ];
+    for (msg, expected) in expected {
+        let (msg, funds) = parse(msg);
+        assert_eq!(msg, expected);
+        assert!(funds.is_empty());
+    }
+}
+
+/// Answers the smart queries sent to "planets" with the given contract
+struct PlanetsQuerier {
+    deps: OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    contract: Cw721Contract<'static, Extension, Empty>,
+}
+
+impl Querier for PlanetsQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        match from_slice(bin_request).unwrap() {
+            QueryRequest::<Empty>::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                assert_eq!(contract_addr, "planets");
+                let msg: QueryMsg = from_binary(&msg).unwrap();
+                let res = self.contract.query(self.deps.as_ref(), mock_env(), msg);
+                SystemResult::Ok(res.into())
+            }
+            request => panic!("unexpected query {:?}", request),
+        }
+    }
+}
+
+#[test]
+fn data_helpers_query_the_contract() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let info = mock_info("venus", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    contract.execute(deps.as_mut(), mock_env(), info, mint_msg).unwrap();
+
+    let querier = PlanetsQuerier { deps, contract };
+    let helper = Cw721DataContract(Addr::unchecked("planets"));
+
+    assert_eq!(helper.admin(&querier).unwrap().admin, ADMIN);
+    assert_eq!(helper.owner_of(&querier, "1", false).unwrap().owner, "venus");
+    assert_eq!(helper.num_tokens(&querier).unwrap().count, 1);
+    assert_eq!(helper.contract_info(&querier).unwrap().symbol, SYMBOL);
+    let info: NftInfoResponse<Extension> = helper.nft_info(&querier, "1").unwrap();
+    assert_eq!(info.extension.name, "Planet #1");
+    let info: AllNftInfoResponse<Extension> = helper.all_nft_info(&querier, "1", false).unwrap();
+    assert_eq!(info.access.owner, "venus");
+    let tokens = helper.tokens(&querier, "venus", None, None).unwrap().tokens;
+    assert_eq!(tokens, vec!["1"]);
+    assert_eq!(helper.all_tokens(&querier, None, None).unwrap().tokens, vec!["1"]);
+    let operators = helper.approved_for_all(&querier, "venus", false, None, None).unwrap();
+    assert!(operators.operators.is_empty());
+    let nfts: NftsDataResponse<Extension> =
+        helper.nfts_data(&querier, vec!["1".to_string(), "2".to_string()]).unwrap();
+    assert_eq!(nfts.missing, vec!["2"]);
+    let page: NftsDataPageResponse<Extension> = helper.all_nfts_data(&querier, None, None).unwrap();
+    assert_eq!(page.nfts.len(), 1);
+    let page: NftsDataPageResponse<Extension> =
+        helper.owner_nfts_data(&querier, "venus", None, None).unwrap();
+    assert_eq!(page.nfts[0].token_id, Some("1".to_string()));
+    let location = helper.planet_location(&querier, "1").unwrap();
+    assert_eq!(location.system_index, 1);
+    let system: SystemPlanetsResponse<Extension> = helper.system_planets(&querier, 1).unwrap();
+    assert_eq!(system.nfts.len(), 1);
+    assert!(helper.recent_edits(&querier, None, None).unwrap().edits.is_empty());
+    let tokens = helper.tokens_by_format(&querier, DataFormat::None, None, None).unwrap();
+    assert_eq!(tokens.tokens, vec!["1"]);
+    assert_eq!(helper.num_tokens_by_format(&querier, DataFormat::Geo1).unwrap().count, 0);
+    assert_eq!(helper.balance(&querier, "venus").unwrap().balance, 1);
+    assert_eq!(helper.holders(&querier, None, None).unwrap().holders, vec![Holder {
+        address: "venus".to_string(),
+        tokens: 1,
+    }]);
+    let status = helper.whitelist_status(&querier, "venus", vec![], None).unwrap();
+    assert!(!status.claimed);
+    assert!(helper.hooks(&querier).unwrap().hooks.is_empty());
+    assert!(helper.sale_phases(&querier, None).unwrap().phases.is_empty());
+    let grants = helper.operator_grants(&querier, "venus", false, None, None).unwrap();
+    assert!(grants.grants.is_empty());
+    let approvals = helper.spender_approvals(&querier, "venus", false, None, None).unwrap();
+    assert!(approvals.approvals.is_empty());
+}
+
+#[test]
+fn hooks_are_notified_of_mints_transfers_and_edits() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+    let admin = mock_info(ADMIN, &[]);
+
+    // only the admin registers hooks
+    let add_hook = |addr: &str| ExecuteMsg::<Extension>::AddHook {
+        addr: addr.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("random", &[]), add_hook("gallery"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    for hook in ["gallery", "game"] {
+        contract
+            .execute(deps.as_mut(), mock_env(), admin.clone(), add_hook(hook))
+            .unwrap();
+    }
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), admin.clone(), add_hook("game"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::HookAlreadyRegistered {});
+    let hooks: HooksResponse =
+        from_binary(&contract.query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap())
+            .unwrap();
+    assert_eq!(hooks.hooks, vec!["gallery", "game"]);
+
+    let notified = |res: Response, msg: PlanetHookMsg| {
+        let expected: Vec<CosmosMsg> = ["gallery", "game"]
+            .iter()
+            .map(|hook| msg.clone().into_cosmos_msg(*hook).unwrap())
+            .collect();
+        let messages: Vec<CosmosMsg> = res.messages.iter().map(|sub| sub.msg.clone()).collect();
+        assert_eq!(messages, expected);
+        // each hook replies with its position when it fails
+        let replies: Vec<_> = res.messages.iter().map(|sub| (sub.id, sub.reply_on.clone())).collect();
+        assert_eq!(replies, vec![(0, ReplyOn::Error), (1, ReplyOn::Error)]);
+    };
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let minter = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+    notified(res, PlanetHookMsg {
+        token_id: "1".to_string(),
+        event: PlanetEvent::Mint,
+        old_owner: None,
+        new_owner: "demeter".to_string(),
+        data_hash: None,
+    });
+
+    let update_msg = ExecuteMsg::Update(UpdateMsg {
+        token_id: "1".to_string(),
+        data: Some("planet".to_string()),
+    });
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), update_msg)
+        .unwrap();
+    // sha256 of "planet"
+    let data_hash = Some("cb5815fe5c3c9d7bf15dbe4964571c528985bb6c3559d1752f11ebd00cfd451f".to_string());
+    notified(res, PlanetHookMsg {
+        token_id: "1".to_string(),
+        event: PlanetEvent::Update,
+        old_owner: Some("demeter".to_string()),
+        new_owner: "demeter".to_string(),
+        data_hash: data_hash.clone(),
+    });
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "ceres".to_string(),
+        token_id: "1".to_string(),
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), transfer_msg)
+        .unwrap();
+    notified(res, PlanetHookMsg {
+        token_id: "1".to_string(),
+        event: PlanetEvent::Transfer,
+        old_owner: Some("demeter".to_string()),
+        new_owner: "ceres".to_string(),
+        data_hash,
+    });
+
+    // a failing hook is logged and does not revert the change
+    let failure = Reply {
+        id: 1,
+        result: ContractResult::Err("out of gas".to_string()),
+    };
+    let res = contract.reply(deps.as_mut(), mock_env(), failure).unwrap();
+    assert_eq!(res.attributes, vec![
+        attr("action", "hook_failed"),
+        attr("hook", "game"),
+        attr("error", "out of gas"),
+    ]);
+    let unknown = Reply {
+        id: 7,
+        result: ContractResult::Err("out of gas".to_string()),
+    };
+    contract.reply(deps.as_mut(), mock_env(), unknown).unwrap_err();
+
+    // removed hooks are no longer notified
+    for hook in ["gallery", "game"] {
+        let remove_hook = ExecuteMsg::RemoveHook {
+            addr: hook.to_string(),
+        };
+        contract
+            .execute(deps.as_mut(), mock_env(), admin.clone(), remove_hook)
+            .unwrap();
+    }
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), admin.clone(), ExecuteMsg::RemoveHook {
+            addr: "game".to_string(),
+        })
+        .unwrap_err();
+    assert_eq!(err, ContractError::HookNotRegistered {});
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "demeter".to_string(),
+        token_id: "1".to_string(),
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info("ceres", &[]), transfer_msg)
+        .unwrap();
+    assert!(res.messages.is_empty());
+
+    // the number of hooks is capped
+    for i in 0..MAX_HOOKS {
+        contract
+            .execute(deps.as_mut(), mock_env(), admin.clone(), add_hook(&format!("hook{}", i)))
+            .unwrap();
+    }
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), admin, add_hook("gallery"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::TooManyHooks { max: MAX_HOOKS });
+}
+
+#[test]
+fn pruning_removes_expired_grants() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+    let owner = mock_info("demeter", &[]);
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    let minter = mock_info("demeter", &[Coin {
+        denom: "uusd".to_string(),
+        amount: offer_amount,
+    }]);
+    for _ in 0..2 {
+        contract
+            .execute(deps.as_mut(), mock_env(), minter.clone(), mint_msg.clone())
+            .unwrap();
+    }
+
+    let approve = |spender: &str, token_id: &str, expires: Expiration| ExecuteMsg::Approve {
+        spender: spender.to_string(),
+        token_id: token_id.to_string(),
+        expires: Some(expires),
+    };
+    let approve_all = |operator: &str, expires: Expiration| ExecuteMsg::ApproveAll {
+        operator: operator.to_string(),
+        expires: Some(expires),
+    };
+    let at_height = |height: u64| {
+        let mut env = mock_env();
+        env.block.height = height;
+        env
+    };
+    let approvals = |deps: Deps, token_id: &str| -> Vec<String> {
+        contract
+            .tokens
+            .load(deps.storage, crate::state::token_key(token_id).unwrap())
+            .unwrap()
+            .approvals
+            .into_iter()
+            .map(|apr| apr.spender.to_string())
+            .collect()
+    };
+    let operators = |deps: Deps| -> Vec<String> {
+        contract
+            .operators
+            .prefix(&Addr::unchecked("demeter"))
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|key| String::from_utf8(key).unwrap())
+            .collect()
+    };
+
+    for msg in [
+        approve("hermes", "1", Expiration::AtHeight(12_400)),
+        approve("apollo", "1", Expiration::Never {}),
+        approve("hermes", "2", Expiration::AtHeight(12_400)),
+        approve_all("ares", Expiration::AtHeight(12_400)),
+        approve_all("hades", Expiration::Never {}),
+        approve_all("zeus", Expiration::AtHeight(12_400)),
+    ] {
+        contract
+            .execute(deps.as_mut(), mock_env(), owner.clone(), msg)
+            .unwrap();
+    }
+
+    // anyone can prune, the limit bounds the grants and the tokens looked at in one call
+    let prune = |operator_start_after: Option<&str>, start_after: Option<&str>, limit| {
+        ExecuteMsg::PruneExpired {
+            owner: "demeter".to_string(),
+            operator_start_after: operator_start_after.map(String::from),
+            start_after: start_after.map(String::from),
+            limit,
+        }
+    };
+    let prune_at = |deps: DepsMut, msg: ExecuteMsg<Extension>| {
+        let res = contract
+            .execute(deps, at_height(13_000), mock_info("random", &[]), msg)
+            .unwrap();
+        res.attributes[2..].to_vec()
+    };
+    let attributes = prune_at(deps.as_mut(), prune(None, None, Some(1)));
+    assert_eq!(attributes, vec![
+        attr("removed", "2"),
+        attr("next_operator_start_after", "ares"),
+        attr("next_start_after", "1"),
+    ]);
+    assert_eq!(operators(deps.as_ref()), vec!["hades", "zeus"]);
+    assert_eq!(approvals(deps.as_ref(), "1"), vec!["apollo"]);
+    assert_eq!(approvals(deps.as_ref(), "2"), vec!["hermes"]);
+
+    let attributes = prune_at(deps.as_mut(), prune(Some("ares"), Some("1"), Some(1)));
+    assert_eq!(attributes, vec![
+        attr("removed", "1"),
+        attr("next_operator_start_after", "hades"),
+        attr("next_start_after", "2"),
+    ]);
+    assert_eq!(operators(deps.as_ref()), vec!["hades", "zeus"]);
+    assert!(approvals(deps.as_ref(), "2").is_empty());
+
+    let attributes = prune_at(deps.as_mut(), prune(Some("hades"), Some("2"), Some(1)));
+    assert_eq!(attributes, vec![
+        attr("removed", "1"),
+        attr("next_operator_start_after", "zeus"),
+        attr("next_start_after", ""),
+    ]);
+    assert_eq!(operators(deps.as_ref()), vec!["hades"]);
+
+    let attributes = prune_at(deps.as_mut(), prune(Some("zeus"), Some("2"), Some(1)));
+    assert_eq!(attributes, vec![
+        attr("removed", "0"),
+        attr("next_operator_start_after", ""),
+        attr("next_start_after", ""),
+    ]);
+    let attributes = prune_at(deps.as_mut(), prune(None, None, None));
+    assert_eq!(attributes[0], attr("removed", "0"));
+
+    // updating the data drops the expired approvals of the token
+    let approve_msg = approve("athena", "1", Expiration::AtHeight(13_100));
+    contract
+        .execute(deps.as_mut(), at_height(13_000), owner.clone(), approve_msg)
+        .unwrap();
+    let update_msg = ExecuteMsg::Update(UpdateMsg {
+        token_id: "1".to_string(),
+        data: Some("planet".to_string()),
+    });
+    contract
+        .execute(deps.as_mut(), at_height(14_000), owner.clone(), update_msg)
+        .unwrap();
+    assert_eq!(approvals(deps.as_ref(), "1"), vec!["apollo"]);
+
+    // and so does approving someone else
+    let approve_msg = approve("athena", "2", Expiration::AtHeight(14_100));
+    contract
+        .execute(deps.as_mut(), at_height(14_000), owner.clone(), approve_msg)
+        .unwrap();
+    let approve_msg = approve("apollo", "2", Expiration::Never {});
+    contract
+        .execute(deps.as_mut(), at_height(15_000), owner, approve_msg)
+        .unwrap();
+    assert_eq!(approvals(deps.as_ref(), "2"), vec!["apollo"]);
+}
+
+#[test]
+fn query_grants_by_operator_and_spender() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        proof: None,
+        voucher: None,
+        salt: None,
+        extension: None,
+    });
+    for owner in ["demeter", "demeter", "ceres"] {
+        let minter = mock_info(owner, &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }]);
+        contract
+            .execute(deps.as_mut(), mock_env(), minter, mint_msg.clone())
+            .unwrap();
+    }
+
+    let grants = [
+        ("demeter", ExecuteMsg::ApproveAll {
+            operator: "market".to_string(),
+            expires: None,
+        }),
+        ("ceres", ExecuteMsg::ApproveAll {
+            operator: "market".to_string(),
+            expires: Some(Expiration::AtHeight(12_400)),
+        }),
+        ("demeter", ExecuteMsg::ApproveAll {
+            operator: "ares".to_string(),
+            expires: None,
+        }),
+        ("demeter", ExecuteMsg::Approve {
+            spender: "market".to_string(),
+            token_id: "1".to_string(),
+            expires: None,
+        }),
+        ("demeter", ExecuteMsg::Approve {
+            spender: "market".to_string(),
+            token_id: "2".to_string(),
+            expires: None,
+        }),
+        ("ceres", ExecuteMsg::Approve {
+            spender: "market".to_string(),
+            token_id: "3".to_string(),
+            expires: Some(Expiration::AtHeight(12_400)),
+        }),
+    ];
+    for (sender, msg) in grants {
+        contract
+            .execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+            .unwrap();
+    }
+
+    let operator_grants = |deps: Deps, env, include_expired| -> Vec<String> {
+        let query_msg = QueryMsg::OperatorGrants {
+            operator: "market".to_string(),
+            include_expired: Some(include_expired),
+            start_after: None,
+            limit: None,
+        };
+        let res: OperatorGrantsResponse =
+            from_binary(&contract.query(deps, env, query_msg).unwrap()).unwrap();
+        res.grants.into_iter().map(|grant| grant.owner).collect()
+    };
+    let spender_approvals = |deps: Deps, start_after: Option<&str>, limit| -> Vec<SpenderApproval> {
+        let query_msg = QueryMsg::SpenderApprovals {
+            spender: "market".to_string(),
+            include_expired: None,
+            start_after: start_after.map(String::from),
+            limit,
+        };
+        let res: SpenderApprovalsResponse =
+            from_binary(&contract.query(deps, mock_env(), query_msg).unwrap()).unwrap();
+        res.approvals
+    };
+    let approval = |token_id: &str, owner: &str, expires| SpenderApproval {
+        token_id: token_id.to_string(),
+        owner: owner.to_string(),
+        expires,
+    };
+
+    let mut later = mock_env();
+    later.block.height = 13_000;
+    assert_eq!(operator_grants(deps.as_ref(), mock_env(), false), vec!["ceres", "demeter"]);
+    assert_eq!(operator_grants(deps.as_ref(), later.clone(), false), vec!["demeter"]);
+    assert_eq!(operator_grants(deps.as_ref(), later, true), vec!["ceres", "demeter"]);
+
+    assert_eq!(spender_approvals(deps.as_ref(), None, Some(2)), vec![
+        approval("1", "demeter", Expiration::Never {}),
+        approval("2", "demeter", Expiration::Never {}),
+    ]);
+    assert_eq!(spender_approvals(deps.as_ref(), Some("2"), Some(2)), vec![approval(
+        "3",
+        "ceres",
+        Expiration::AtHeight(12_400)
+    )]);
+
+    // revoking and transferring drop the grants from the reverse indexes
+    let revoke_all = ExecuteMsg::RevokeAll {
+        operator: "market".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), revoke_all)
+        .unwrap();
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: "hermes".to_string(),
+        token_id: "1".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), transfer_msg)
+        .unwrap();
+    let revoke = ExecuteMsg::Revoke {
+        spender: "market".to_string(),
+        token_id: "2".to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info("demeter", &[]), revoke)
+        .unwrap();
+    assert_eq!(operator_grants(deps.as_ref(), mock_env(), false), vec!["ceres"]);
+    assert_eq!(spender_approvals(deps.as_ref(), None, None), vec![approval(
+        "3",
+        "ceres",
+        Expiration::AtHeight(12_400)
+    )]);
+
+    // migrating a current storage layout leaves the indexes alone
+    contract
+        .operator_grants
+        .remove(deps.as_mut().storage, (&Addr::unchecked("market"), &Addr::unchecked("ceres")));
+    contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    assert!(operator_grants(deps.as_ref(), mock_env(), false).is_empty());
+
+    // migrating an older one indexes the grants stored before the reverse index existed
+    contract.storage_layout.save(deps.as_mut().storage, &1).unwrap();
+    contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    assert_eq!(operator_grants(deps.as_ref(), mock_env(), false), vec!["ceres"]);
+}
+
+#[test]
+fn migrating_counts_claimed_flags() {
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+    let demeter = Addr::unchecked("demeter");
+    let ceres = Addr::unchecked("ceres");
+
+    // storage layout 1 kept a flag per address
+    contract.storage_layout.save(deps.as_mut().storage, &1).unwrap();
+    contract
+        .legacy_claimed
+        .save(deps.as_mut().storage, &demeter, &true)
+        .unwrap();
+    contract
+        .legacy_claimed
+        .save(deps.as_mut().storage, &ceres, &false)
+        .unwrap();
+
+    contract
+        .migrate(deps.as_mut(), mock_env(), MigrateMsg {})
+        .unwrap();
+    assert_eq!(contract.claimed.load(&deps.storage, &demeter).unwrap(), 1);
+    assert_eq!(contract.claimed.may_load(&deps.storage, &ceres).unwrap(), None);
+    assert_eq!(contract.storage_layout.load(&deps.storage).unwrap(), STORAGE_LAYOUT);
+}
+
+#[test]
+fn whitelist_vouchers_allow_their_allocation() {
+    let offer_amount = Uint128::from(25000000u128);
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract_whitelist(deps.as_mut());
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(1639526632);
+
+    let signer = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+    let public_key = Binary::from(signer.verifying_key().to_bytes().to_vec());
+    let voucher = |contract_addr: &str, minter: &str, phase: &str, allocation: u64, expires| {
+        let message = voucher::voucher_message(contract_addr, minter, phase, allocation, expires);
+        let signature: Signature = signer.sign(&message);
+        WhitelistVoucher {
+            allocation,
+            expires,
+            signature: Binary::from(signature.as_ref().to_vec()),
+        }
+    };
+    let mint_msg = |voucher: WhitelistVoucher| {
+        ExecuteMsg::Mint(MintMsg::<Extension> {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: offer_amount,
+            },
+            proof: None,
+            voucher: Some(voucher),
+            salt: None,
+            extension: None,
+        })
+    };
+    let minter = |sender: &str| {
+        mock_info(sender, &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }])
+    };
+    let zeus_voucher = voucher(MOCK_CONTRACT_ADDR, "zeus", "", 2, 1639600000);
+
+    // vouchers are refused until a signer is set
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), minter("zeus"), mint_msg(zeus_voucher.clone()))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoWhitelistSigner {});
+
+    let set_signer = |public_key: Option<Binary>| ExecuteMsg::UpdateWhitelistSigner { public_key };
+    let admin = mock_info(ADMIN, &[]);
+    let signer_msg = set_signer(Some(public_key.clone()));
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), mock_info("zeus", &[]), signer_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let signer_msg = set_signer(Some(Binary::from(vec![2, 1])));
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), admin.clone(), signer_msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidPublicKey {});
+    contract
+        .execute(deps.as_mut(), env.clone(), admin.clone(), set_signer(Some(public_key)))
+        .unwrap();
+
+    // the voucher is good for its allocation
+    for _ in 0..2 {
+        contract
+            .execute(deps.as_mut(), env.clone(), minter("zeus"), mint_msg(zeus_voucher.clone()))
+            .unwrap();
+    }
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), minter("zeus"), mint_msg(zeus_voucher.clone()))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Claimed {});
+    assert_eq!(contract.claimed.load(&deps.storage, &Addr::unchecked("zeus")).unwrap(), 2);
+
+    // and only for the minter and the contract it was signed for, until it expires
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), minter("hera"), mint_msg(zeus_voucher))
+        .unwrap_err();
+    assert_eq!(err, ContractError::VerificationFailed {});
+    let other_contract = voucher("other", "hera", "", 1, 1639600000);
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), minter("hera"), mint_msg(other_contract))
+        .unwrap_err();
+    assert_eq!(err, ContractError::VerificationFailed {});
+    let expired = voucher(MOCK_CONTRACT_ADDR, "hera", "", 1, 1639526632);
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), minter("hera"), mint_msg(expired))
+        .unwrap_err();
+    assert_eq!(err, ContractError::VoucherExpired {});
+
+    // the status query checks vouchers like mint
+    let status_of = |deps: Deps, address: &str, voucher: WhitelistVoucher| {
+        let query_msg = QueryMsg::WhitelistStatus {
+            address: address.to_string(),
+            proof: vec![],
+            voucher: Some(voucher),
+        };
+        let res: WhitelistStatusResponse =
+            from_binary(&contract.query(deps, env.clone(), query_msg).unwrap()).unwrap();
+        res
+    };
+    let zeus_voucher = voucher(MOCK_CONTRACT_ADDR, "zeus", "", 2, 1639600000);
+    let res = status_of(deps.as_ref(), "zeus", zeus_voucher.clone());
+    assert_eq!((res.valid, res.claimed, res.minted, res.cap), (true, true, 2, Some(2)));
+    assert_eq!(res.remaining, Some(0));
+    let hera_voucher = voucher(MOCK_CONTRACT_ADDR, "hera", "", 3, 1639600000);
+    let res = status_of(deps.as_ref(), "hera", hera_voucher);
+    assert_eq!((res.valid, res.cap, res.remaining), (true, Some(3), Some(3)));
+    let res = status_of(deps.as_ref(), "hera", zeus_voucher);
+    assert_eq!((res.valid, res.remaining), (false, Some(0)));
+
+    // with sale phases a voucher is only good in the phase it was signed for
+    let og = SalePhase {
+        name: "og".to_string(),
+        start_time: 1639500000,
+        end_time: Some(1639550000),
+        whitelist: PhaseWhitelist::Voucher,
+        price: 25000000,
+        wallet_cap: None,
+        supply_cap: None,
+    };
+    let allowlist = SalePhase {
+        name: "allowlist".to_string(),
+        start_time: 1639550000,
+        end_time: None,
+        ..og.clone()
+    };
+    let phases_msg = ExecuteMsg::UpdateSalePhases {
+        phases: vec![og, allowlist],
+    };
+    contract
+        .execute(deps.as_mut(), env.clone(), admin.clone(), phases_msg)
+        .unwrap();
+    let mut proof_mint = mint_msg(voucher(MOCK_CONTRACT_ADDR, "hera", "og", 2, 1639600000));
+    if let ExecuteMsg::Mint(mint) = &mut proof_mint {
+        mint.voucher = None;
+        mint.proof = Some(whitelist_proof());
+    }
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), minter("hera"), proof_mint)
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoVoucher {});
+    let unphased = voucher(MOCK_CONTRACT_ADDR, "hera", "", 2, 1639600000);
+    let err = contract
+        .execute(deps.as_mut(), env.clone(), minter("hera"), mint_msg(unphased))
+        .unwrap_err();
+    assert_eq!(err, ContractError::VerificationFailed {});
+    let og_voucher = voucher(MOCK_CONTRACT_ADDR, "hera", "og", 2, 1639600000);
+    contract
+        .execute(deps.as_mut(), env.clone(), minter("hera"), mint_msg(og_voucher.clone()))
+        .unwrap();
+    let res = status_of(deps.as_ref(), "hera", og_voucher.clone());
+    assert_eq!(res.phase, Some("og".to_string()));
+    assert_eq!((res.valid, res.minted, res.cap, res.remaining), (true, 1, None, Some(1)));
+
+    // the allocation covers every phase, the voucher of one phase is refused in the next
+    let mut next_phase = env.clone();
+    next_phase.block.time = Timestamp::from_seconds(1639560000);
+    let err = contract
+        .execute(deps.as_mut(), next_phase.clone(), minter("hera"), mint_msg(og_voucher))
+        .unwrap_err();
+    assert_eq!(err, ContractError::VerificationFailed {});
+    let allowlist_voucher = voucher(MOCK_CONTRACT_ADDR, "hera", "allowlist", 2, 1639600000);
+    contract
+        .execute(
+            deps.as_mut(),
+            next_phase.clone(),
+            minter("hera"),
+            mint_msg(allowlist_voucher.clone()),
+        )
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), next_phase, minter("hera"), mint_msg(allowlist_voucher))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Claimed {});
+    assert_eq!(contract.claimed.load(&deps.storage, &Addr::unchecked("hera")).unwrap(), 2);
+
+    // removing the signer stops the vouchers
+    contract
+        .execute(deps.as_mut(), env.clone(), admin, set_signer(None))
+        .unwrap();
+    let hera_voucher = voucher(MOCK_CONTRACT_ADDR, "hera", "og", 1, 1639600000);
+    let err = contract
+        .execute(deps.as_mut(), env, minter("hera"), mint_msg(hera_voucher))
+        .unwrap_err();
+    assert_eq!(err, ContractError::NoWhitelistSigner {});
+}
+
+#[test]
+fn sale_phases_pick_the_active_phase() {
+    let mut deps = mock_dependencies(&[]);
+    let contract = setup_contract(deps.as_mut());
+    let admin = mock_info(ADMIN, &[]);
+    let allowlist = MerkleTree::new(&["zeus".to_string(), "hera".to_string()]).unwrap();
+
+    let phase = |name: &str, start_time: u64, end_time: Option<u64>, price: u64| SalePhase {
+        name: name.to_string(),
+        start_time,
+        end_time,
+        whitelist: PhaseWhitelist::None,
+        price,
+        wallet_cap: None,
+        supply_cap: None,
+    };
+    let phases = vec![
+        SalePhase {
+            whitelist: PhaseWhitelist::Merkle {
+                root: allowlist.root(),
+            },
+            wallet_cap: Some(1),
+            ..phase("og", 1000, Some(2000), 10000000)
+        },
+        SalePhase {
+            wallet_cap: Some(2),
+            supply_cap: Some(3),
+            ..phase("public", 2000, Some(3000), 25000000)
+        },
+        phase("last chance", 3000, None, 30000000),
+    ];
+    let at = |seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(seconds);
+        env
+    };
+    let mint = |sender: &str, price: u128, proof: Option<Vec<String>>| {
+        let offer_amount = Uint128::from(price);
+        let msg = ExecuteMsg::Mint(MintMsg::<Extension> {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: offer_amount,
+            },
+            proof,
+            voucher: None,
+            salt: None,
+            extension: None,
+        });
+        let info = mock_info(sender, &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }]);
+        (info, msg)
+    };
+
+    // only the admin sets the phases, and they must not overlap
+    let update = |phases: Vec<SalePhase>| ExecuteMsg::<Extension>::UpdateSalePhases { phases };
+    let err = contract
+        .execute(deps.as_mut(), at(0), mock_info("zeus", &[]), update(phases.clone()))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let overlapping = vec![phases[0].clone(), phase("public", 1500, None, 25000000)];
+    let err = contract
+        .execute(deps.as_mut(), at(0), admin.clone(), update(overlapping))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSalePhase {
+        name: "public".to_string(),
+        reason: "overlaps the previous phase".to_string(),
+    });
+    let duplicate = vec![phases[0].clone(), phase("og", 2000, None, 25000000)];
+    let err = contract
+        .execute(deps.as_mut(), at(0), admin.clone(), update(duplicate))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSalePhase {
+        name: "og".to_string(),
+        reason: "duplicate name".to_string(),
+    });
+    let uncapped = vec![SalePhase {
+        wallet_cap: None,
+        ..phases[0].clone()
+    }];
+    let err = contract
+        .execute(deps.as_mut(), at(0), admin.clone(), update(uncapped))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSalePhase {
+        name: "og".to_string(),
+        reason: "merkle whitelist without a wallet cap".to_string(),
+    });
+    contract
+        .execute(deps.as_mut(), at(0), admin.clone(), update(phases.clone()))
+        .unwrap();
+
+    // nothing is for sale before the first phase
+    let (info, msg) = mint("zeus", 10000000, Some(allowlist.proof(0)));
+    let err = contract.execute(deps.as_mut(), at(500), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::SaleNotActive {});
+
+    // the og phase takes its own price and root, once per wallet
+    let (info, msg) = mint("hera", 25000000, Some(allowlist.proof(1)));
+    let err = contract.execute(deps.as_mut(), at(1500), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let (info, msg) = mint("hera", 10000000, Some(allowlist.proof(1)));
+    contract.execute(deps.as_mut(), at(1500), info, msg).unwrap();
+    let (info, msg) = mint("hera", 10000000, Some(allowlist.proof(1)));
+    let err = contract.execute(deps.as_mut(), at(1500), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::WalletCapReached {});
+    let (info, msg) = mint("ares", 10000000, Some(allowlist.proof(0)));
+    let err = contract.execute(deps.as_mut(), at(1500), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::MerkleVerification {});
+    let (info, msg) = mint("ares", 10000000, None);
+    let err = contract.execute(deps.as_mut(), at(1500), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::NoProof {});
+
+    // the whitelist status follows the active phase
+    let whitelist_status = |deps: Deps, seconds: u64, address: &str, proof: Vec<String>| {
+        let msg = QueryMsg::WhitelistStatus {
+            address: address.to_string(),
+            proof,
+            voucher: None,
+        };
+        let res: WhitelistStatusResponse =
+            from_binary(&contract.query(deps, at(seconds), msg).unwrap()).unwrap();
+        res
+    };
+    assert_eq!(
+        whitelist_status(deps.as_ref(), 1500, "hera", allowlist.proof(1)),
+        WhitelistStatusResponse {
+            phase: Some("og".to_string()),
+            merkle_root: Some(allowlist.root()),
+            valid: true,
+            claimed: true,
+            minted: 1,
+            cap: Some(1),
+            remaining: Some(0),
+        }
+    );
+    assert_eq!(
+        whitelist_status(deps.as_ref(), 1500, "zeus", allowlist.proof(0)).remaining,
+        Some(1)
+    );
+    assert_eq!(
+        whitelist_status(deps.as_ref(), 1500, "ares", allowlist.proof(0)).remaining,
+        Some(0)
+    );
+    let msg = QueryMsg::WhitelistStatus {
+        address: "zeus".to_string(),
+        proof: allowlist.proof(0),
+        voucher: None,
+    };
+    let err = contract.query(deps.as_ref(), at(500), msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err(ContractError::SaleNotActive {}.to_string()));
+
+    // the public phase is open to everyone up to its caps
+    for _ in 0..2 {
+        let (info, msg) = mint("zeus", 25000000, None);
+        contract.execute(deps.as_mut(), at(2500), info, msg).unwrap();
+    }
+    let (info, msg) = mint("zeus", 25000000, None);
+    let err = contract.execute(deps.as_mut(), at(2500), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::WalletCapReached {});
+    let (info, msg) = mint("hera", 25000000, None);
+    contract.execute(deps.as_mut(), at(2500), info, msg).unwrap();
+    let (info, msg) = mint("ares", 25000000, None);
+    let err = contract.execute(deps.as_mut(), at(2500), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::PhaseSoldOut {});
+    let res = whitelist_status(deps.as_ref(), 2500, "zeus", vec![]);
+    assert_eq!((res.merkle_root, res.valid, res.minted, res.remaining), (None, true, 2, Some(0)));
+    let res = whitelist_status(deps.as_ref(), 2500, "ares", vec![]);
+    assert_eq!((res.claimed, res.cap, res.remaining), (false, Some(2), Some(0)));
+
+    let query = |deps: Deps, seconds: u64, address: Option<&str>| -> SalePhasesResponse {
+        let msg = QueryMsg::SalePhases {
+            address: address.map(String::from),
+        };
+        from_binary(&contract.query(deps, at(seconds), msg).unwrap()).unwrap()
+    };
+    let status = |index: usize, active: bool, minted: u64, minted_by_address: Option<u64>| {
+        SalePhaseStatus {
+            phase: phases[index].clone(),
+            active,
+            minted,
+            minted_by_address,
+        }
+    };
+    assert_eq!(query(deps.as_ref(), 2500, Some("zeus")), SalePhasesResponse {
+        phases: vec![
+            status(0, false, 1, Some(0)),
+            status(1, true, 3, Some(2)),
+            status(2, false, 0, Some(0)),
+        ],
+    });
+
+    // the last phase runs until the supply is minted
+    let (info, msg) = mint("ares", 30000000, None);
+    contract.execute(deps.as_mut(), at(3500), info, msg).unwrap();
+    assert_eq!(query(deps.as_ref(), 3500, None).phases[2], status(2, true, 1, None));
+
+    // without phases the contract price and mint times apply again
+    contract
+        .execute(deps.as_mut(), at(3500), admin.clone(), update(vec![]))
+        .unwrap();
+    assert!(query(deps.as_ref(), 3500, None).phases.is_empty());
+    let (info, msg) = mint("ares", 25000000, None);
+    contract.execute(deps.as_mut(), at(3500), info, msg).unwrap();
+
+    // a new phase cannot take over the counts of a removed one
+    let err = contract
+        .execute(deps.as_mut(), at(3500), admin.clone(), update(vec![phases[1].clone()]))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSalePhase {
+        name: "public".to_string(),
+        reason: "name of a removed phase".to_string(),
+    });
+    let encore = vec![phase("encore", 4000, None, 30000000)];
+    contract
+        .execute(deps.as_mut(), at(3500), admin, update(encore))
+        .unwrap();
+}
@@ -33,9 +33,48 @@ pub enum ContractError {
     #[error("No Proof")]
     NoProof {},
 
+    #[error("No whitelist voucher")]
+    NoVoucher {},
+
     #[error("Not enough funds to cover the tax")]
     FundsTooSmall {},
 
     #[error("No Balance in that denomination")]
     NoFunds {},
+
+    #[error("Invalid planet name")]
+    InvalidName {},
+
+    #[error("Invalid planet description")]
+    InvalidDescription {},
+
+    #[error("Hook already registered")]
+    HookAlreadyRegistered {},
+
+    #[error("Hook not registered")]
+    HookNotRegistered {},
+
+    #[error("Cannot register more than {max} hooks")]
+    TooManyHooks { max: usize },
+
+    #[error("Whitelist vouchers are not accepted")]
+    NoWhitelistSigner {},
+
+    #[error("Whitelist voucher expired")]
+    VoucherExpired {},
+
+    #[error("Invalid secp256k1 public key")]
+    InvalidPublicKey {},
+
+    #[error("No sale phase is active")]
+    SaleNotActive {},
+
+    #[error("Sale phase sold out")]
+    PhaseSoldOut {},
+
+    #[error("Mint limit of the sale phase reached for this address")]
+    WalletCapReached {},
+
+    #[error("Invalid sale phase {name}: {reason}")]
+    InvalidSalePhase { name: String, reason: String },
 }
@@ -1,10 +1,15 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Timestamp, Uint128, Coin, CosmosMsg, BankMsg, Decimal, QuerierWrapper, entry_point};
+use cosmwasm_std::{Addr, Binary, ContractResult, Deps, DepsMut, Env, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg, Timestamp, Uint128, Coin, CosmosMsg, BankMsg, Decimal, QuerierWrapper};
 
 use cw2::set_contract_version;
-use cw721::{ContractInfoResponse, CustomMsg, Cw721Execute, Cw721ReceiveMsg, Expiration};
+use cw0::maybe_addr;
+use cw_storage_plus::{Bound, U64Key};
+use cw721::{
+    ContractInfoResponse, CustomMsg, Cw721Execute, Cw721ReceiveMsg, Expiration, PlanetEvent,
+    PlanetHookMsg,
+};
 use sha2::Digest;
 use std::convert::TryInto;
 use terraswap::asset::{AssetInfo};
@@ -12,12 +17,24 @@ use terraswap::querier::query_balance;
 use terra_cosmwasm::TerraQuerier;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, UpdateMsg, MigrateMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::merkle;
+use crate::voucher;
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, MigrateMsg, MintMsg, PhaseWhitelist, SalePhase, UpdateMsg,
+    WhitelistVoucher,
+};
+use crate::state::{
+    token_bound, token_id_from_key, token_key, Approval, Cw721Contract, DataFormat, PlanetProfile, TokenInfo, MAX_HOOKS,
+    STORAGE_LAYOUT,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw721-data";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_DESCRIPTION_LENGTH: usize = 256;
+const DEFAULT_PRUNE_LIMIT: u32 = 30;
+const MAX_PRUNE_LIMIT: u32 = 100;
 static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
 
 impl<'a, T, C> Cw721Contract<'a, T, C>
@@ -39,6 +56,7 @@ where
             symbol: msg.symbol,
         };      
         self.contract_info.save(deps.storage, &info)?;
+        self.storage_layout.save(deps.storage, &STORAGE_LAYOUT)?;
 
         //Contrat admin
         let admin = deps.api.addr_validate(&msg.admin)?;
@@ -50,15 +68,48 @@ where
         self.price.save(deps.storage, &Uint128::from(msg.price))?;
         self.token_count.save(deps.storage, &0)?;
         self.merkle_root.save(deps.storage, &msg.merkle_root)?;
+        if let Some(public_key) = msg.whitelist_signer {
+            validate_public_key(&public_key).map_err(|err| StdError::generic_err(err.to_string()))?;
+            self.whitelist_signer.save(deps.storage, &public_key)?;
+        }
         self.token_uri.save(deps.storage, &msg.token_uri)?;
         let whitelist_mint_time = Timestamp::from_seconds(msg.whitelist_mint_time);
         self.whitelist_mint_time.save(deps.storage, &whitelist_mint_time)?;
         let open_mint_time = Timestamp::from_seconds(msg.open_mint_time);
         self.open_mint_time.save(deps.storage, &open_mint_time)?;
+        self.random_mint.save(deps.storage, &msg.random_mint.unwrap_or(false))?;
+        if let Some(website_url) = msg.website_url {
+            let website_url = website_url.trim_end_matches('/').to_string();
+            self.website_url.save(deps.storage, &website_url)?;
+        }
+        if let Some(phases) = msg.sale_phases {
+            validate_sale_phases(&phases).map_err(|err| StdError::generic_err(err.to_string()))?;
+            self.sale_phases.save(deps.storage, &phases)?;
+        }
 
         Ok(Response::default())
     }
 
+    pub fn migrate(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        _msg: MigrateMsg,
+    ) -> Result<Response<C>, ContractError> {
+        set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+        let layout = self.storage_layout.may_load(deps.storage)?.unwrap_or_default();
+        if layout < STORAGE_LAYOUT {
+            //Move the state out of the namespaces the misordered storage keys put it in
+            self.migrate_storage_layout(deps.storage)?;
+            //Index the operators by who they were granted to, the tokens are moved to numeric
+            //keys in batches with MigrateTokens
+            self.rebuild_operator_grants(deps.storage)?;
+        }
+
+        Ok(Response::new().add_attribute("action", "migrate"))
+    }
+
     pub fn execute(
         &self,
         deps: DepsMut,
@@ -69,6 +120,11 @@ where
         match msg {
             ExecuteMsg::Mint(msg) => self.mint(deps, env, info, msg),
             ExecuteMsg::Update(msg) => self.update_data(deps, env, info, msg),
+            ExecuteMsg::SetProfile {
+                token_id,
+                name,
+                description,
+            } => self.set_profile(deps, env, info, token_id, name, description),
             ExecuteMsg::Approve {
                 spender,
                 token_id,
@@ -92,6 +148,34 @@ where
             } => self.send_nft(deps, env, info, contract, token_id, msg),
             ExecuteMsg::Withdraw { denom } => self.withdraw(deps, env, info, denom),
             ExecuteMsg::UpdatePrice { price } => self.update_price(deps, env, info, price),
+            ExecuteMsg::UpdateWebsiteUrl { website_url } => {
+                self.update_website_url(deps, env, info, website_url)
+            }
+            ExecuteMsg::AddHook { addr } => self.add_hook(deps, env, info, addr),
+            ExecuteMsg::RemoveHook { addr } => self.remove_hook(deps, env, info, addr),
+            ExecuteMsg::PruneExpired {
+                owner,
+                operator_start_after,
+                start_after,
+                limit,
+            } => self.prune_expired(
+                deps,
+                env,
+                info,
+                owner,
+                operator_start_after,
+                start_after,
+                limit,
+            ),
+            ExecuteMsg::MigrateTokens { start_after, limit } => {
+                self.execute_migrate_tokens(deps, env, info, start_after, limit)
+            }
+            ExecuteMsg::UpdateWhitelistSigner { public_key } => {
+                self.update_whitelist_signer(deps, env, info, public_key)
+            }
+            ExecuteMsg::UpdateSalePhases { phases } => {
+                self.update_sale_phases(deps, env, info, phases)
+            }
         }
     }
 }
@@ -104,21 +188,34 @@ where
 {
     pub fn mint(
         &self,
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
         msg: MintMsg<T>,
     ) -> Result<Response<C>, ContractError> {
         let max_issuance = self.max_issuance.load(deps.storage)?;
         let count = self.token_count.load(deps.storage)?;
-        let price = self.price.load(deps.storage)?;
-        let whitelist_mint_time = self.whitelist_mint_time.load(deps.storage)?;
-        let open_mint_time = self.open_mint_time.load(deps.storage)?;
         //Check if there are NFTs left to print
         if count >= max_issuance {
             return Err(ContractError::MaxIssued {});
         }
 
+        //The active sale phase sets the price, the contract price applies without phases
+        let phases = self.sale_phases.may_load(deps.storage)?.unwrap_or_default();
+        let phase = if phases.is_empty() {
+            None
+        } else {
+            let phase = phases
+                .into_iter()
+                .find(|phase| phase.is_active(env.block.time))
+                .ok_or(ContractError::SaleNotActive {})?;
+            Some(phase)
+        };
+        let price = match &phase {
+            Some(phase) => Uint128::from(phase.price),
+            None => self.price.load(deps.storage)?,
+        };
+
         if msg.offer_asset.amount != price {
             return Err(ContractError:: Unauthorized {});
         }
@@ -138,61 +235,51 @@ where
             return Err(ContractError:: Unauthorized {});
         }
 
-        if env.block.time < whitelist_mint_time {
+        if let Some(phase) = &phase {
+            self.record_phase_mint(deps.branch(), &env, &info.sender, &msg, phase)?;
+        } else if env.block.time < self.whitelist_mint_time.load(deps.storage)? {
             return Err(ContractError:: Unauthorized {});
-        } else if env.block.time < open_mint_time {
-            if msg.proof.is_none() {
-                return Err(ContractError:: NoProof {});
-            }
+        } else if env.block.time < self.open_mint_time.load(deps.storage)? {
+            let claimed = self
+                .claimed
+                .may_load(deps.storage, &info.sender)?
+                .unwrap_or_default();
 
-            //check if already minted
-            let claimed = self.claimed.may_load(deps.storage, &info.sender)?;
-            if claimed.is_some() {
-                return Err(ContractError::Claimed {});
-            }
+            if let Some(voucher) = &msg.voucher {
+                //vouchers allow as many mints as their allocation
+                self.check_voucher(deps.as_ref(), &env, &info.sender, "", voucher)?;
+                if claimed >= voucher.allocation {
+                    return Err(ContractError::Claimed {});
+                }
+            } else {
+                let proof = msg.proof.as_ref().ok_or(ContractError::NoProof {})?;
 
-            let proof = msg.proof.unwrap();
-            let merkle_root: String = self.merkle_root.load(deps.storage)?;
-            let user_input: String = info.sender.to_string();
-            let mut hash: [u8; 32] = sha2::Sha256::digest(user_input.as_bytes())
-                .as_slice()
-                .try_into()
-                .expect("Wrong length");
-
-            for p in proof {
-                let mut proof_buf: [u8; 32] = [0; 32];
-                match hex::decode_to_slice(p, &mut proof_buf) {
-                    Ok(()) => {}
-                    _ => return Err(ContractError::InvalidHexProof {}),
+                //check if already minted
+                if claimed > 0 {
+                    return Err(ContractError::Claimed {});
                 }
 
-                hash = if bytes_cmp(hash, proof_buf) == std::cmp::Ordering::Less {
-                    sha2::Sha256::digest(&[hash, proof_buf].concat())
-                        .as_slice()
-                        .try_into()
-                        .expect("Wrong length")
-                } else {
-                    sha2::Sha256::digest(&[proof_buf, hash].concat())
-                        .as_slice()
-                        .try_into()
-                        .expect("Wrong length")
-                };
+                let merkle_root: String = self.merkle_root.load(deps.storage)?;
+                if !merkle::verify_proof(&merkle_root, info.sender.as_str(), proof)? {
+                    return Err(ContractError::MerkleVerification {});
+                }
             }
 
-            let mut root_buf: [u8; 32] = [0; 32];
-            hex::decode_to_slice(merkle_root, &mut root_buf).unwrap();
-            if root_buf != hash {
-                return Err(ContractError::MerkleVerification {});
-            }
-            
-            //Add to address to the claimed map
-            self.claimed.save(deps.storage, &info.sender, &true)?;
+            //Count the whitelist mint of the address
+            self.claimed.save(deps.storage, &info.sender, &(claimed + 1))?;
         }
 
         //Set the info for the token
         let owner = info.sender.to_string();
         let token_uri = self.token_uri.load(deps.storage)?;
-        let token_id = (count + 1).to_string();
+        let random_mint = self.random_mint.may_load(deps.storage)?.unwrap_or(false);
+        let token_id = if random_mint {
+            let seed = mint_entropy(&env, &info.sender, msg.salt.as_deref(), count);
+            self.draw_token_id(deps.storage, max_issuance - count, seed)?
+        } else {
+            count + 1
+        }
+        .to_string();
         //create the token
         let token = TokenInfo {
             owner: deps.api.addr_validate(&owner)?,
@@ -202,40 +289,176 @@ where
             extension: msg.extension.clone(),
         };
         self.tokens
-            .update(deps.storage, &token_id, |old| match old {
+            .update(deps.storage, token_key(&token_id)?, |old| match old {
                 Some(_) => Err(ContractError::Claimed {}),
                 None => Ok(token),
             })?;
 
         self.increment_tokens(deps.storage)?;
+        self.update_format_count(deps.storage, None, DataFormat::None)?;
+        self.move_balance(deps.storage, None, &info.sender)?;
+
+        let hooks = self.hook_messages(
+            deps.storage,
+            PlanetHookMsg {
+                token_id: token_id.clone(),
+                event: PlanetEvent::Mint,
+                old_owner: None,
+                new_owner: owner,
+                data_hash: None,
+            },
+        )?;
 
         Ok(Response::new()
+            .add_submessages(hooks)
             .add_attribute("action", "mint")
             .add_attribute("minter", info.sender)
             .add_attribute("token_id", token_id))
     }
 
+    /// Checks the caps and whitelist of the active sale phase and counts the mint in it
+    fn record_phase_mint(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        sender: &Addr,
+        msg: &MintMsg<T>,
+        phase: &SalePhase,
+    ) -> Result<(), ContractError> {
+        let minted = self
+            .phase_minted
+            .may_load(deps.storage, &phase.name)?
+            .unwrap_or_default();
+        if matches!(phase.supply_cap, Some(cap) if minted >= cap) {
+            return Err(ContractError::PhaseSoldOut {});
+        }
+        let claimed = self
+            .phase_claims
+            .may_load(deps.storage, (&phase.name, sender))?
+            .unwrap_or_default();
+        if matches!(phase.wallet_cap, Some(cap) if claimed >= cap) {
+            return Err(ContractError::WalletCapReached {});
+        }
+
+        //Whitelisted phases take a proof against their own root, or a voucher signed for them
+        match &phase.whitelist {
+            PhaseWhitelist::None => {}
+            PhaseWhitelist::Merkle { root } => {
+                let proof = msg.proof.as_ref().ok_or(ContractError::NoProof {})?;
+                if !merkle::verify_proof(root, sender.as_str(), proof)? {
+                    return Err(ContractError::MerkleVerification {});
+                }
+            }
+            PhaseWhitelist::Voucher => {
+                //the allocation covers the voucher mints of every phase, like `claimed`
+                let voucher = msg.voucher.as_ref().ok_or(ContractError::NoVoucher {})?;
+                self.check_voucher(deps.as_ref(), env, sender, &phase.name, voucher)?;
+                let used = self.claimed.may_load(deps.storage, sender)?.unwrap_or_default();
+                if used >= voucher.allocation {
+                    return Err(ContractError::Claimed {});
+                }
+                self.claimed.save(deps.storage, sender, &(used + 1))?;
+            }
+        }
+
+        self.phase_minted.save(deps.storage, &phase.name, &(minted + 1))?;
+        self.phase_claims
+            .save(deps.storage, (&phase.name, sender), &(claimed + 1))?;
+        Ok(())
+    }
+
+    /// Checks that the voucher is unexpired and signed for `sender` and `phase` by the
+    /// whitelist signer
+    fn check_voucher(
+        &self,
+        deps: Deps,
+        env: &Env,
+        sender: &Addr,
+        phase: &str,
+        voucher: &WhitelistVoucher,
+    ) -> Result<(), ContractError> {
+        let signer = self
+            .whitelist_signer
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoWhitelistSigner {})?;
+        voucher::check_voucher(deps.api, env, &signer, sender, phase, voucher)
+    }
+
     pub fn update_data(
         &self,
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
         msg: UpdateMsg,
     ) -> Result<Response<C>, ContractError> {
-        let mut token = self.tokens.load(deps.storage, &msg.token_id)?;
+        let mut token = self.tokens.load(deps.storage, token_key(&msg.token_id)?)?;
         //check if the sender has permission to update the planet
-        self.check_can_update(deps.as_ref(), &_env, &info, &token)?;
+        self.check_can_update(deps.as_ref(), &env, &info, &token)?;
         //update the data
+        let old_format = DataFormat::of(&token.data);
         token.data = msg.data;
+        //drop the approvals that ran out while the token is written anyway
+        let approvals = token.approvals.clone();
+        token.approvals.retain(|apr| !apr.is_expired(&env.block));
         //save the updates
-        self.tokens.save(deps.storage, &msg.token_id, &token)?;
+        let key = token_key(&msg.token_id)?;
+        self.tokens.save(deps.storage, key.clone(), &token)?;
+        self.update_spender_approvals(deps.storage, &key, &approvals, &token.approvals)?;
+        self.update_format_count(deps.storage, Some(old_format), DataFormat::of(&token.data))?;
+        //add the edit to the recent edits feed
+        self.record_edit(deps.storage, &msg.token_id, &info.sender, env.block.time)?;
+
+        let hooks = self.hook_messages(
+            deps.storage,
+            PlanetHookMsg {
+                token_id: msg.token_id.clone(),
+                event: PlanetEvent::Update,
+                old_owner: Some(token.owner.to_string()),
+                new_owner: token.owner.to_string(),
+                data_hash: data_hash(&token.data),
+            },
+        )?;
 
         Ok(Response::new()
+            .add_submessages(hooks)
             .add_attribute("action", "update_data")
             .add_attribute("owner", info.sender)
             .add_attribute("token_id", msg.token_id))
     }
 
+    pub fn set_profile(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        name: Option<String>,
+        description: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, token_key(&token_id)?)?;
+        //check if the sender has permission to update the planet
+        self.check_can_update(deps.as_ref(), &env, &info, &token)?;
+
+        if let Some(name) = &name {
+            validate_name(name)?;
+        }
+        if let Some(description) = &description {
+            validate_description(description)?;
+        }
+
+        if name.is_none() && description.is_none() {
+            self.profiles.remove(deps.storage, &token_id);
+        } else {
+            let profile = PlanetProfile { name, description };
+            self.profiles.save(deps.storage, &token_id, &profile)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_profile")
+            .add_attribute("owner", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
     pub fn withdraw(
         &self,
         deps: DepsMut,
@@ -288,6 +511,252 @@ where
         Ok(Response::new()
             .add_attribute("withdraw", price.to_string()))
     }
+
+    pub fn update_website_url(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        website_url: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        //Check if the sender is the addmin
+        let admin = self.admin.load(deps.storage)?;
+        if admin != info.sender {
+            return Err(ContractError:: Unauthorized {});
+        }
+
+        let website_url = website_url.map(|url| url.trim_end_matches('/').to_string());
+        match &website_url {
+            Some(url) => self.website_url.save(deps.storage, url)?,
+            None => self.website_url.remove(deps.storage),
+        }
+        Ok(Response::new()
+            .add_attribute("action", "update_website_url")
+            .add_attribute("website_url", website_url.unwrap_or_default()))
+    }
+
+    pub fn update_whitelist_signer(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        public_key: Option<Binary>,
+    ) -> Result<Response<C>, ContractError> {
+        //Check if the sender is the addmin
+        let admin = self.admin.load(deps.storage)?;
+        if admin != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        match &public_key {
+            Some(public_key) => {
+                validate_public_key(public_key)?;
+                self.whitelist_signer.save(deps.storage, public_key)?;
+            }
+            None => self.whitelist_signer.remove(deps.storage),
+        }
+        Ok(Response::new()
+            .add_attribute("action", "update_whitelist_signer")
+            .add_attribute("public_key", public_key.map(|key| key.to_base64()).unwrap_or_default()))
+    }
+
+    pub fn update_sale_phases(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        phases: Vec<SalePhase>,
+    ) -> Result<Response<C>, ContractError> {
+        //Check if the sender is the addmin
+        let admin = self.admin.load(deps.storage)?;
+        if admin != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        validate_sale_phases(&phases)?;
+        //Mints are counted by phase name, a removed phase must not lend its counts to a new one
+        let current = self.sale_phases.may_load(deps.storage)?.unwrap_or_default();
+        for phase in &phases {
+            let kept = current.iter().any(|other| other.name == phase.name);
+            if !kept && self.phase_minted.has(deps.storage, &phase.name) {
+                return Err(ContractError::InvalidSalePhase {
+                    name: phase.name.clone(),
+                    reason: "name of a removed phase".to_string(),
+                });
+            }
+        }
+        if phases.is_empty() {
+            self.sale_phases.remove(deps.storage);
+        } else {
+            self.sale_phases.save(deps.storage, &phases)?;
+        }
+        Ok(Response::new()
+            .add_attribute("action", "update_sale_phases")
+            .add_attribute("phases", phases.len().to_string()))
+    }
+
+    /// Deletes the expired grants among the next `limit` operator grants of `owner` after
+    /// `operator_start_after`, and the expired approvals of its next `limit` tokens after
+    /// `start_after`. Anyone can call it
+    #[allow(clippy::too_many_arguments)]
+    pub fn prune_expired(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        _info: MessageInfo,
+        owner: String,
+        operator_start_after: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+        let owner_addr = deps.api.addr_validate(&owner)?;
+
+        //expired grants among the next `limit` operators of the owner
+        let start_addr = maybe_addr(deps.api, operator_start_after)?;
+        let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
+        let grants: Vec<(Vec<u8>, Expiration)> = self
+            .operators
+            .prefix(&owner_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<_>>()?;
+        let operators: Vec<&Vec<u8>> = grants
+            .iter()
+            .filter(|(_, expires)| expires.is_expired(&env.block))
+            .map(|(operator, _)| operator)
+            .collect();
+        for operator in &operators {
+            let operator = String::from_utf8(operator.to_vec()).map_err(StdError::invalid_utf8)?;
+            self.remove_operator(deps.storage, &owner_addr, &Addr::unchecked(operator));
+        }
+        let mut removed = operators.len();
+
+        //expired approvals of the next `limit` tokens of the owner
+        let start = token_bound(start_after)?;
+        let keys: Vec<Vec<u8>> = self
+            .tokens
+            .idx
+            .owner
+            .prefix(owner_addr)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect();
+        for key in &keys {
+            let key = U64Key::from(key.clone());
+            let mut token = self.tokens.load(deps.storage, key.clone())?;
+            let approvals = token.approvals.clone();
+            token.approvals.retain(|apr| !apr.is_expired(&env.block));
+            if token.approvals.len() < approvals.len() {
+                self.tokens.save(deps.storage, key.clone(), &token)?;
+                self.update_spender_approvals(deps.storage, &key, &approvals, &token.approvals)?;
+                removed += approvals.len() - token.approvals.len();
+            }
+        }
+
+        //a full batch may leave grants or tokens behind, hand out the cursors to the next one
+        let next_operator_start_after = match grants.last() {
+            Some((operator, _)) if grants.len() == limit => {
+                String::from_utf8(operator.clone()).map_err(StdError::invalid_utf8)?
+            }
+            _ => String::new(),
+        };
+        let next_start_after = match keys.last() {
+            Some(key) if keys.len() == limit => token_id_from_key(key)?,
+            _ => String::new(),
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "prune_expired")
+            .add_attribute("owner", owner)
+            .add_attribute("removed", removed.to_string())
+            .add_attribute("next_operator_start_after", next_operator_start_after)
+            .add_attribute("next_start_after", next_start_after))
+    }
+
+    pub fn execute_migrate_tokens(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        //Check if the sender is the addmin
+        let admin = self.admin.load(deps.storage)?;
+        if admin != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+        let moved = self.migrate_tokens(deps.storage, start_after, limit)?;
+
+        //a full batch may leave tokens behind, hand out the cursor to the next one
+        let next_start_after = match moved.last() {
+            Some(token_id) if moved.len() == limit => token_id.clone(),
+            _ => String::new(),
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate_tokens")
+            .add_attribute("moved", moved.len().to_string())
+            .add_attribute("next_start_after", next_start_after))
+    }
+
+    pub fn add_hook(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        addr: String,
+    ) -> Result<Response<C>, ContractError> {
+        //Check if the sender is the addmin
+        let admin = self.admin.load(deps.storage)?;
+        if admin != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let hook = deps.api.addr_validate(&addr)?;
+        let mut hooks = self.hooks.may_load(deps.storage)?.unwrap_or_default();
+        if hooks.contains(&hook) {
+            return Err(ContractError::HookAlreadyRegistered {});
+        }
+        if hooks.len() >= MAX_HOOKS {
+            return Err(ContractError::TooManyHooks { max: MAX_HOOKS });
+        }
+        hooks.push(hook);
+        self.hooks.save(deps.storage, &hooks)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "add_hook")
+            .add_attribute("hook", addr))
+    }
+
+    pub fn remove_hook(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        addr: String,
+    ) -> Result<Response<C>, ContractError> {
+        //Check if the sender is the addmin
+        let admin = self.admin.load(deps.storage)?;
+        if admin != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let hook = deps.api.addr_validate(&addr)?;
+        let mut hooks = self.hooks.may_load(deps.storage)?.unwrap_or_default();
+        if !hooks.contains(&hook) {
+            return Err(ContractError::HookNotRegistered {});
+        }
+        hooks.retain(|registered| *registered != hook);
+        self.hooks.save(deps.storage, &hooks)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_hook")
+            .add_attribute("hook", addr))
+    }
 }
 
 impl<'a, T, C> Cw721Execute<T, C> for Cw721Contract<'a, T, C>
@@ -305,9 +774,10 @@ where
         recipient: String,
         token_id: String,
     ) -> Result<Response<C>, ContractError> {
-        self._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
+        let hooks = self._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
 
         Ok(Response::new()
+            .add_submessages(hooks)
             .add_attribute("action", "transfer_nft")
             .add_attribute("sender", info.sender)
             .add_attribute("recipient", recipient)
@@ -324,7 +794,7 @@ where
         msg: Binary,
     ) -> Result<Response<C>, ContractError> {
         // Transfer token
-        self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
+        let hooks = self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
 
         let send = Cw721ReceiveMsg {
             sender: info.sender.to_string(),
@@ -334,6 +804,7 @@ where
 
         // Send message
         Ok(Response::new()
+            .add_submessages(hooks)
             .add_message(send.into_cosmos_msg(contract.clone())?)
             .add_attribute("action", "send_nft")
             .add_attribute("sender", info.sender)
@@ -392,8 +863,7 @@ where
 
         // set the operator for us
         let operator_addr = deps.api.addr_validate(&operator)?;
-        self.operators
-            .save(deps.storage, (&info.sender, &operator_addr), &expires)?;
+        self.save_operator(deps.storage, &info.sender, &operator_addr, &expires)?;
 
         Ok(Response::new()
             .add_attribute("action", "approve_all")
@@ -409,8 +879,7 @@ where
         operator: String,
     ) -> Result<Response<C>, ContractError> {
         let operator_addr = deps.api.addr_validate(&operator)?;
-        self.operators
-            .remove(deps.storage, (&info.sender, &operator_addr));
+        self.remove_operator(deps.storage, &info.sender, &operator_addr);
 
         Ok(Response::new()
             .add_attribute("action", "revoke_all")
@@ -425,6 +894,7 @@ where
     T: Serialize + DeserializeOwned + Clone,
     C: CustomMsg,
 {
+    /// Moves the token to `recipient`, returns the submessages notifying the hooks
     pub fn _transfer_nft(
         &self,
         deps: DepsMut,
@@ -432,15 +902,64 @@ where
         info: &MessageInfo,
         recipient: &str,
         token_id: &str,
-    ) -> Result<TokenInfo<T>, ContractError> {
-        let mut token = self.tokens.load(deps.storage, &token_id)?;
+    ) -> Result<Vec<SubMsg<C>>, ContractError> {
+        let mut token = self.tokens.load(deps.storage, token_key(token_id)?)?;
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
         // set owner and remove existing approvals
+        let previous_owner = token.owner;
         token.owner = deps.api.addr_validate(recipient)?;
-        token.approvals = vec![];
-        self.tokens.save(deps.storage, &token_id, &token)?;
-        Ok(token)
+        let approvals = std::mem::take(&mut token.approvals);
+        let key = token_key(token_id)?;
+        self.tokens.save(deps.storage, key.clone(), &token)?;
+        self.update_spender_approvals(deps.storage, &key, &approvals, &[])?;
+        self.move_balance(deps.storage, Some(&previous_owner), &token.owner)?;
+
+        let hooks = self.hook_messages(
+            deps.storage,
+            PlanetHookMsg {
+                token_id: token_id.to_string(),
+                event: PlanetEvent::Transfer,
+                old_owner: Some(previous_owner.to_string()),
+                new_owner: token.owner.to_string(),
+                data_hash: data_hash(&token.data),
+            },
+        )?;
+        Ok(hooks)
+    }
+
+    /// One submessage per registered hook contract, replying with the position of the hook
+    /// in `hooks` when it fails so the failure is logged instead of reverting the transaction
+    pub fn hook_messages(
+        &self,
+        storage: &dyn Storage,
+        msg: PlanetHookMsg,
+    ) -> StdResult<Vec<SubMsg<C>>> {
+        self.hooks
+            .may_load(storage)?
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .map(|(index, hook)| {
+                let hook_msg = msg.clone().into_cosmos_msg(hook)?;
+                Ok(SubMsg::reply_on_error(hook_msg, index as u64))
+            })
+            .collect()
+    }
+
+    /// Logs a failed hook call, the planet change it was notified of still goes through
+    pub fn reply(&self, deps: DepsMut, _env: Env, msg: Reply) -> Result<Response<C>, ContractError> {
+        let hooks = self.hooks.may_load(deps.storage)?.unwrap_or_default();
+        let hook = hooks
+            .get(msg.id as usize)
+            .ok_or_else(|| StdError::generic_err(format!("Unknown reply id: {}", msg.id)))?;
+        match msg.result {
+            ContractResult::Err(error) => Ok(Response::new()
+                .add_attribute("action", "hook_failed")
+                .add_attribute("hook", hook)
+                .add_attribute("error", error)),
+            ContractResult::Ok(_) => Ok(Response::new()),
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -455,17 +974,18 @@ where
         add: bool,
         expires: Option<Expiration>,
     ) -> Result<TokenInfo<T>, ContractError> {
-        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        let key = token_key(token_id)?;
+        let mut token = self.tokens.load(deps.storage, key.clone())?;
         // ensure we have permissions
         self.check_can_approve(deps.as_ref(), env, info, &token)?;
+        let approvals = token.approvals.clone();
 
-        // update the approval list (remove any for the same spender before adding)
+        // update the approval list (remove any for the same spender before adding,
+        // and the expired ones while we are at it)
         let spender_addr = deps.api.addr_validate(spender)?;
-        token.approvals = token
+        token
             .approvals
-            .into_iter()
-            .filter(|apr| apr.spender != spender_addr)
-            .collect();
+            .retain(|apr| apr.spender != spender_addr && !apr.is_expired(&env.block));
 
         // only difference between approve and revoke
         if add {
@@ -481,7 +1001,8 @@ where
             token.approvals.push(approval);
         }
 
-        self.tokens.save(deps.storage, &token_id, &token)?;
+        self.tokens.save(deps.storage, key.clone(), &token)?;
+        self.update_spender_approvals(deps.storage, &key, &approvals, &token.approvals)?;
 
         Ok(token)
     }
@@ -496,7 +1017,7 @@ where
     ) -> Result<(), ContractError> {
         // owner can send
         if token.owner == info.sender {
-            return Ok(());
+            Ok(())
         } else {
             Err(ContractError::Unauthorized {})
         }
@@ -569,17 +1090,96 @@ where
     }
 }
 
-fn bytes_cmp(a: [u8; 32], b: [u8; 32]) -> std::cmp::Ordering {
-    let mut i = 0;
-    while i < 32 {
-        match a[i].cmp(&b[i]) {
-            std::cmp::Ordering::Greater => return std::cmp::Ordering::Greater,
-            std::cmp::Ordering::Less => return std::cmp::Ordering::Less,
-            _ => i += 1,
+/// Names are 1 to 32 characters of letters, digits, spaces, '-', '.' and '\''
+/// without leading or trailing spaces
+fn validate_name(name: &str) -> Result<(), ContractError> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH || name.trim() != name {
+        return Err(ContractError::InvalidName {});
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '.' | '\''))
+    {
+        return Err(ContractError::InvalidName {});
+    }
+    Ok(())
+}
+
+/// Descriptions are up to 256 bytes of text, new lines are the only control characters allowed
+fn validate_description(description: &str) -> Result<(), ContractError> {
+    if description.len() > MAX_DESCRIPTION_LENGTH {
+        return Err(ContractError::InvalidDescription {});
+    }
+    if description.chars().any(|c| c.is_control() && c != '\n') {
+        return Err(ContractError::InvalidDescription {});
+    }
+    Ok(())
+}
+
+/// Compressed (33 bytes) or uncompressed (65 bytes) secp256k1 public key
+fn validate_public_key(public_key: &Binary) -> Result<(), ContractError> {
+    match (public_key.len(), public_key.first()) {
+        (33, Some(0x02)) | (33, Some(0x03)) | (65, Some(0x04)) => Ok(()),
+        _ => Err(ContractError::InvalidPublicKey {}),
+    }
+}
+
+/// Phases need unique names, a valid merkle root when whitelisted, and must follow each
+/// other in start order, only the last one may run without an end time
+fn validate_sale_phases(phases: &[SalePhase]) -> Result<(), ContractError> {
+    for (index, phase) in phases.iter().enumerate() {
+        let invalid = |reason: &str| ContractError::InvalidSalePhase {
+            name: phase.name.clone(),
+            reason: reason.to_string(),
+        };
+        if phase.name.is_empty() {
+            return Err(invalid("empty name"));
+        }
+        if phases[..index].iter().any(|other| other.name == phase.name) {
+            return Err(invalid("duplicate name"));
+        }
+        if matches!(phase.end_time, Some(end_time) if end_time <= phase.start_time) {
+            return Err(invalid("ends before it starts"));
+        }
+        if let PhaseWhitelist::Merkle { root } = &phase.whitelist {
+            if !matches!(hex::decode(root), Ok(root) if root.len() == 32) {
+                return Err(invalid("invalid merkle root"));
+            }
+            if phase.wallet_cap.is_none() {
+                return Err(invalid("merkle whitelist without a wallet cap"));
+            }
+        }
+        if index > 0 {
+            match phases[index - 1].end_time {
+                Some(end_time) if end_time <= phase.start_time => {}
+                _ => return Err(invalid("overlaps the previous phase")),
+            }
         }
     }
+    Ok(())
+}
+
+/// Hex encoded sha256 of the planet data, as sent to the hooks
+fn data_hash(data: &Option<String>) -> Option<String> {
+    data.as_ref()
+        .map(|data| hex::encode(sha2::Sha256::digest(data.as_bytes())))
+}
 
-    std::cmp::Ordering::Equal
+/// Seed of the id shuffle. Every input is known to the sender when the mint runs, so a
+/// contract can simulate the draw and revert until it gets the id it wants
+fn mint_entropy(env: &Env, sender: &Addr, salt: Option<&str>, count: u64) -> [u8; 32] {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.nanos().to_be_bytes());
+    hasher.update(env.block.chain_id.as_bytes());
+    hasher.update(sender.as_bytes());
+    hasher.update(salt.unwrap_or_default().as_bytes());
+    hasher.update(count.to_be_bytes());
+    hasher
+        .finalize()
+        .as_slice()
+        .try_into()
+        .expect("Wrong length")
 }
 
 fn compute_tax(querier: &QuerierWrapper, amount: Uint128, denom: String) -> StdResult<Uint128> {
@@ -598,8 +1198,3 @@ fn compute_tax(querier: &QuerierWrapper, amount: Uint128, denom: String) -> StdR
         tax_cap,
     ))
 }
-
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
-}
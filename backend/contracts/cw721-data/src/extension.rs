@@ -1,9 +1,9 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use cw721::{OwnerOfResponse, NftDataExtension};
+use cw721::{NftDataExtension, OwnerOfResponse};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct NftDataResponse {
+pub struct NftDataResponse<T> {
     /// Who can transfer the token
     pub access: OwnerOfResponse,
     /// Data on the token itself,
@@ -13,15 +13,31 @@ pub struct NftDataResponse {
     pub token_uri: Option<String>,
     pub data: Option<String>,
     /// You can add any custom metadata here when you extend cw721-base
-    pub extension: NftDataExtension,
+    pub extension: NftDataExtension<T>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct NftsDataResponse {
-    /// Returns all planets asked for in a single query
-    pub nfts: Option<Vec<NftDataResponse>>,
+pub struct NftsDataResponse<T> {
+    /// Returns all minted planets asked for in a single query, in the requested order
+    pub nfts: Option<Vec<NftDataResponse<T>>>,
+    /// Requested ids that are not minted, in the requested order
+    #[serde(default)]
+    pub missing: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NftsDataPageResponse<T> {
+    /// Data and ownership of the planets of this page, ordered by token id
+    pub nfts: Vec<NftDataResponse<T>>,
+    /// Pass as `start_after` to get the next page, unset on the last page
+    pub next_start_after: Option<String>,
+}
 
-
-
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SystemPlanetsResponse<T> {
+    pub system_index: u64,
+    /// Every planet id of the system ordered by slot, minted or not
+    pub token_ids: Vec<String>,
+    /// Data and ownership of the minted planets of the system
+    pub nfts: Vec<NftDataResponse<T>>,
+}
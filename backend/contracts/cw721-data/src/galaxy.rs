@@ -0,0 +1,85 @@
+//! Deterministic layout of the galaxy, ported from the frontend `GalacticSpec`.
+//! Planets are grouped in star systems of 3, 4 and 5 planets in a repeating cycle and
+//! the systems are spread over the arms of the galaxy. Token and system ids start at 1.
+
+pub const ARMS: u64 = 4;
+pub const CYCLE_SIZE: u64 = 12;
+pub const SYSTEM_SIZES: [u64; 3] = [3, 4, 5];
+pub const STAR_COLORS: [&str; 5] = ["Blue", "White", "Orange", "Red", "Purple"];
+
+pub const SYSTEM_ADJECTIVES: [&str; 36] = [
+    "Chunky", "Galactic", "Spicy", "Thick", "Raging", "Renegade", "Corrupted", "Holy", "Cosmic",
+    "Delicious", "Crunchy", "Fantastic", "Superior", "Haunted", "Spooky", "Seductive", "Bullish",
+    "Bearish", "Rekt", "Hot", "Cold", "Doughy", "Stable", "Sleepy", "Toxic", "Anonymous", "Poor",
+    "Rich", "Gassy", "Minty", "Ergonomic", "Quick", "Mighty", "Meaty", "Awkward", "Electric",
+];
+
+pub const SYSTEM_NAMES: [&str; 36] = [
+    "Lunatic", "Apollo", "Spectrum", "Anchor", "Mirror", "Cosmos", "Pylon", "Hermes", "Valkyrie",
+    "Kwon", "Nexus", "Mars", "Talis", "Angel", "Whale", "Terran", "Ozone", "Alice", "Nebula",
+    "Shin", "Nakomoto", "Buterin", "Turtle", "Doge", "Chai", "Candle", "Shill", "HODL", "Chain",
+    "Pool", "Coin", "Rug", "Astrochad", "Exchange", "Prism", "Terrestrial",
+];
+
+/// Index in `SYSTEM_SIZES` of the system holding the planet within its cycle
+fn size_index(planet_id: u64) -> usize {
+    let offset = (planet_id - 1) % CYCLE_SIZE;
+    let mut sum = 0;
+    for (i, size) in SYSTEM_SIZES.iter().enumerate() {
+        sum += size;
+        if sum > offset {
+            return i;
+        }
+    }
+    unreachable!("system sizes add up to the cycle size")
+}
+
+/// Id of the first planet of a system
+fn first_planet(system_id: u64) -> u64 {
+    let index = (system_id - 1) as usize % SYSTEM_SIZES.len();
+    let cycle = (system_id - 1) / SYSTEM_SIZES.len() as u64;
+    cycle * CYCLE_SIZE + SYSTEM_SIZES[..index].iter().sum::<u64>() + 1
+}
+
+/// Star system the planet belongs to
+pub fn planet_system(planet_id: u64) -> u64 {
+    (planet_id - 1) / CYCLE_SIZE * SYSTEM_SIZES.len() as u64 + size_index(planet_id) as u64 + 1
+}
+
+/// Slot of the planet in its system, 0 is the orbit closest to the star
+pub fn planet_position(planet_id: u64) -> u64 {
+    planet_id - first_planet(planet_system(planet_id))
+}
+
+/// Galaxy arm of the system, from 0 to `ARMS - 1`
+pub fn system_arm(system_id: u64) -> u64 {
+    (system_id - 1) % ARMS
+}
+
+pub fn system_name(system_id: u64) -> String {
+    let index = (system_id - 1) as usize;
+    let cycle = index / SYSTEM_NAMES.len();
+    let adjective = SYSTEM_ADJECTIVES[(cycle + index) % SYSTEM_ADJECTIVES.len()];
+    let noun = SYSTEM_NAMES[(cycle * 2 + index) % SYSTEM_NAMES.len()];
+    format!("{} {} System", adjective, noun)
+}
+
+pub fn system_star_color(system_id: u64) -> &'static str {
+    let arm_index = ((system_id - 1) / ARMS) as usize;
+    STAR_COLORS[arm_index % STAR_COLORS.len()]
+}
+
+/// Planet ids of a system, ordered by slot and capped at `max_planets`
+pub fn system_planets(system_id: u64, max_planets: u64) -> Vec<u64> {
+    let first = first_planet(system_id);
+    let size = SYSTEM_SIZES[(system_id - 1) as usize % SYSTEM_SIZES.len()];
+    (first..first + size).filter(|id| *id <= max_planets).collect()
+}
+
+/// Number of systems needed to hold `planet_count` planets
+pub fn total_systems(planet_count: u64) -> u64 {
+    if planet_count == 0 {
+        return 0;
+    }
+    planet_system(planet_count)
+}
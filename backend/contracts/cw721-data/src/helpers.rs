@@ -0,0 +1,524 @@
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, CosmosMsg, Querier, QuerierWrapper, StdResult, WasmMsg,
+    WasmQuery,
+};
+use cw0::Expiration;
+use cw721::{
+    AllNftInfoResponse, ApprovedForAllResponse, ContractInfoResponse, Cw721Contract,
+    NftInfoResponse, NumTokensResponse, OwnerOfResponse, TokensResponse,
+};
+use terraswap::asset::{Asset, AssetInfo};
+
+use crate::extension::{NftsDataPageResponse, NftsDataResponse, SystemPlanetsResponse};
+use crate::msg::{
+    AdminResponse, BalanceResponse, ExecuteMsg, HoldersResponse, HooksResponse, MintMsg,
+    OperatorGrantsResponse, PlanetLocationResponse, QueryMsg, RecentEditsResponse, SalePhase,
+    SalePhasesResponse, SpenderApprovalsResponse, UpdateMsg, WhitelistStatusResponse,
+    WhitelistVoucher,
+};
+use crate::state::DataFormat;
+
+/// Cw721DataContract wraps the address of a cw721-data contract with typed builders
+/// for its execute messages and typed methods for its queries. `.cw721()` gives the
+/// base cw721 helper
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cw721DataContract(pub Addr);
+
+impl Cw721DataContract {
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn cw721(&self) -> Cw721Contract {
+        Cw721Contract(self.addr())
+    }
+
+    pub fn call<T: Serialize>(
+        &self,
+        msg: ExecuteMsg<T>,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = to_binary(&msg)?;
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg,
+            funds,
+        }
+        .into())
+    }
+
+    fn call_base(&self, msg: ExecuteMsg<()>) -> StdResult<CosmosMsg> {
+        self.call(msg, vec![])
+    }
+
+    pub fn query<Q: Querier, T: DeserializeOwned>(
+        &self,
+        querier: &Q,
+        req: QueryMsg,
+    ) -> StdResult<T> {
+        let query = WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&req)?,
+        }
+        .into();
+        QuerierWrapper::new(querier).query(&query)
+    }
+
+    /*** messages ***/
+
+    pub fn transfer_nft<T: Into<String>, U: Into<String>>(
+        &self,
+        recipient: T,
+        token_id: U,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::TransferNft {
+            recipient: recipient.into(),
+            token_id: token_id.into(),
+        })
+    }
+
+    pub fn send_nft<T: Into<String>, U: Into<String>>(
+        &self,
+        contract: T,
+        token_id: U,
+        msg: Binary,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::SendNft {
+            contract: contract.into(),
+            token_id: token_id.into(),
+            msg,
+        })
+    }
+
+    pub fn approve<T: Into<String>, U: Into<String>>(
+        &self,
+        spender: T,
+        token_id: U,
+        expires: Option<Expiration>,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::Approve {
+            spender: spender.into(),
+            token_id: token_id.into(),
+            expires,
+        })
+    }
+
+    pub fn revoke<T: Into<String>, U: Into<String>>(
+        &self,
+        spender: T,
+        token_id: U,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::Revoke {
+            spender: spender.into(),
+            token_id: token_id.into(),
+        })
+    }
+
+    pub fn approve_all<T: Into<String>>(
+        &self,
+        operator: T,
+        expires: Option<Expiration>,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::ApproveAll {
+            operator: operator.into(),
+            expires,
+        })
+    }
+
+    pub fn revoke_all<T: Into<String>>(&self, operator: T) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::RevokeAll {
+            operator: operator.into(),
+        })
+    }
+
+    /// Mints a planet paying `price`, which is both the offered asset and the attached funds.
+    /// `proof` is only needed during the whitelist mint
+    pub fn mint<T: Serialize>(
+        &self,
+        price: Coin,
+        proof: Option<Vec<String>>,
+        salt: Option<String>,
+        extension: T,
+    ) -> StdResult<CosmosMsg> {
+        self.mint_msg(price, proof, None, salt, extension)
+    }
+
+    /// Mints a planet during the whitelist mint with a voucher instead of a merkle proof
+    pub fn mint_with_voucher<T: Serialize>(
+        &self,
+        price: Coin,
+        voucher: WhitelistVoucher,
+        salt: Option<String>,
+        extension: T,
+    ) -> StdResult<CosmosMsg> {
+        self.mint_msg(price, None, Some(voucher), salt, extension)
+    }
+
+    fn mint_msg<T: Serialize>(
+        &self,
+        price: Coin,
+        proof: Option<Vec<String>>,
+        voucher: Option<WhitelistVoucher>,
+        salt: Option<String>,
+        extension: T,
+    ) -> StdResult<CosmosMsg> {
+        let offer_asset = Asset {
+            info: AssetInfo::NativeToken {
+                denom: price.denom.clone(),
+            },
+            amount: price.amount,
+        };
+        let msg = ExecuteMsg::Mint(MintMsg {
+            proof,
+            offer_asset,
+            voucher,
+            salt,
+            extension,
+        });
+        self.call(msg, vec![price])
+    }
+
+    pub fn update<T: Into<String>>(
+        &self,
+        token_id: T,
+        data: Option<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::Update(UpdateMsg {
+            token_id: token_id.into(),
+            data,
+        }))
+    }
+
+    pub fn set_profile<T: Into<String>>(
+        &self,
+        token_id: T,
+        name: Option<String>,
+        description: Option<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::SetProfile {
+            token_id: token_id.into(),
+            name,
+            description,
+        })
+    }
+
+    pub fn withdraw<T: Into<String>>(&self, denom: T) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::Withdraw {
+            denom: denom.into(),
+        })
+    }
+
+    pub fn update_price(&self, price: u64) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::UpdatePrice { price })
+    }
+
+    pub fn update_website_url(&self, website_url: Option<String>) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::UpdateWebsiteUrl { website_url })
+    }
+
+    pub fn update_whitelist_signer(&self, public_key: Option<Binary>) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::UpdateWhitelistSigner { public_key })
+    }
+
+    pub fn update_sale_phases(&self, phases: Vec<SalePhase>) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::UpdateSalePhases { phases })
+    }
+
+    pub fn add_hook<T: Into<String>>(&self, addr: T) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::AddHook { addr: addr.into() })
+    }
+
+    pub fn remove_hook<T: Into<String>>(&self, addr: T) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::RemoveHook { addr: addr.into() })
+    }
+
+    pub fn prune_expired<T: Into<String>>(
+        &self,
+        owner: T,
+        operator_start_after: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::PruneExpired {
+            owner: owner.into(),
+            operator_start_after,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn migrate_tokens(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<CosmosMsg> {
+        self.call_base(ExecuteMsg::MigrateTokens { start_after, limit })
+    }
+
+    /*** queries ***/
+
+    pub fn owner_of<Q: Querier, T: Into<String>>(
+        &self,
+        querier: &Q,
+        token_id: T,
+        include_expired: bool,
+    ) -> StdResult<OwnerOfResponse> {
+        let req = QueryMsg::OwnerOf {
+            token_id: token_id.into(),
+            include_expired: Some(include_expired),
+        };
+        self.query(querier, req)
+    }
+
+    pub fn approved_for_all<Q: Querier, T: Into<String>>(
+        &self,
+        querier: &Q,
+        owner: T,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ApprovedForAllResponse> {
+        let req = QueryMsg::ApprovedForAll {
+            owner: owner.into(),
+            include_expired: Some(include_expired),
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
+    pub fn num_tokens<Q: Querier>(&self, querier: &Q) -> StdResult<NumTokensResponse> {
+        self.query(querier, QueryMsg::NumTokens {})
+    }
+
+    pub fn contract_info<Q: Querier>(&self, querier: &Q) -> StdResult<ContractInfoResponse> {
+        self.query(querier, QueryMsg::ContractInfo {})
+    }
+
+    pub fn nft_info<Q: Querier, T: Into<String>, U: DeserializeOwned>(
+        &self,
+        querier: &Q,
+        token_id: T,
+    ) -> StdResult<NftInfoResponse<U>> {
+        let req = QueryMsg::NftInfo {
+            token_id: token_id.into(),
+        };
+        self.query(querier, req)
+    }
+
+    pub fn all_nft_info<Q: Querier, T: Into<String>, U: DeserializeOwned>(
+        &self,
+        querier: &Q,
+        token_id: T,
+        include_expired: bool,
+    ) -> StdResult<AllNftInfoResponse<U>> {
+        let req = QueryMsg::AllNftInfo {
+            token_id: token_id.into(),
+            include_expired: Some(include_expired),
+        };
+        self.query(querier, req)
+    }
+
+    pub fn tokens<Q: Querier, T: Into<String>>(
+        &self,
+        querier: &Q,
+        owner: T,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let req = QueryMsg::Tokens {
+            owner: owner.into(),
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
+    pub fn all_tokens<Q: Querier>(
+        &self,
+        querier: &Q,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        self.query(querier, QueryMsg::AllTokens { start_after, limit })
+    }
+
+    pub fn admin<Q: Querier>(&self, querier: &Q) -> StdResult<AdminResponse> {
+        self.query(querier, QueryMsg::Admin {})
+    }
+
+    /// Planets that are not minted are listed in `missing`
+    pub fn nfts_data<Q: Querier, U: DeserializeOwned>(
+        &self,
+        querier: &Q,
+        token_ids: Vec<String>,
+    ) -> StdResult<NftsDataResponse<U>> {
+        self.query(querier, QueryMsg::NftsData { token_ids })
+    }
+
+    pub fn all_nfts_data<Q: Querier, U: DeserializeOwned>(
+        &self,
+        querier: &Q,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<NftsDataPageResponse<U>> {
+        let req = QueryMsg::AllNftsData { start_after, limit };
+        self.query(querier, req)
+    }
+
+    pub fn owner_nfts_data<Q: Querier, T: Into<String>, U: DeserializeOwned>(
+        &self,
+        querier: &Q,
+        owner: T,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<NftsDataPageResponse<U>> {
+        let req = QueryMsg::OwnerNftsData {
+            owner: owner.into(),
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
+    pub fn hooks<Q: Querier>(&self, querier: &Q) -> StdResult<HooksResponse> {
+        self.query(querier, QueryMsg::Hooks {})
+    }
+
+    /// Sale phases with their usage, and the usage of `address` when given
+    pub fn sale_phases<Q: Querier>(
+        &self,
+        querier: &Q,
+        address: Option<String>,
+    ) -> StdResult<SalePhasesResponse> {
+        self.query(querier, QueryMsg::SalePhases { address })
+    }
+
+    /// Owners whose tokens `operator` can transfer
+    pub fn operator_grants<Q: Querier, T: Into<String>>(
+        &self,
+        querier: &Q,
+        operator: T,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OperatorGrantsResponse> {
+        let req = QueryMsg::OperatorGrants {
+            operator: operator.into(),
+            include_expired: Some(include_expired),
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
+    /// Tokens `spender` is approved for
+    pub fn spender_approvals<Q: Querier, T: Into<String>>(
+        &self,
+        querier: &Q,
+        spender: T,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<SpenderApprovalsResponse> {
+        let req = QueryMsg::SpenderApprovals {
+            spender: spender.into(),
+            include_expired: Some(include_expired),
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
+    /// Also answers for planets that are not minted yet
+    pub fn planet_location<Q: Querier, T: Into<String>>(
+        &self,
+        querier: &Q,
+        token_id: T,
+    ) -> StdResult<PlanetLocationResponse> {
+        let req = QueryMsg::PlanetLocation {
+            token_id: token_id.into(),
+        };
+        self.query(querier, req)
+    }
+
+    pub fn system_planets<Q: Querier, U: DeserializeOwned>(
+        &self,
+        querier: &Q,
+        system_index: u64,
+    ) -> StdResult<SystemPlanetsResponse<U>> {
+        self.query(querier, QueryMsg::SystemPlanets { system_index })
+    }
+
+    /// Newest first
+    pub fn recent_edits<Q: Querier>(
+        &self,
+        querier: &Q,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<RecentEditsResponse> {
+        self.query(querier, QueryMsg::RecentEdits { start_after, limit })
+    }
+
+    pub fn tokens_by_format<Q: Querier>(
+        &self,
+        querier: &Q,
+        format: DataFormat,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let req = QueryMsg::TokensByFormat {
+            format,
+            start_after,
+            limit,
+        };
+        self.query(querier, req)
+    }
+
+    pub fn num_tokens_by_format<Q: Querier>(
+        &self,
+        querier: &Q,
+        format: DataFormat,
+    ) -> StdResult<NumTokensResponse> {
+        self.query(querier, QueryMsg::NumTokensByFormat { format })
+    }
+
+    pub fn balance<Q: Querier, T: Into<String>>(
+        &self,
+        querier: &Q,
+        owner: T,
+    ) -> StdResult<BalanceResponse> {
+        let req = QueryMsg::Balance {
+            owner: owner.into(),
+        };
+        self.query(querier, req)
+    }
+
+    pub fn holders<Q: Querier>(
+        &self,
+        querier: &Q,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<HoldersResponse> {
+        self.query(querier, QueryMsg::Holders { start_after, limit })
+    }
+
+    /// Checks `voucher` when given, `proof` otherwise, the way `Mint` would
+    pub fn whitelist_status<Q: Querier, T: Into<String>>(
+        &self,
+        querier: &Q,
+        address: T,
+        proof: Vec<String>,
+        voucher: Option<WhitelistVoucher>,
+    ) -> StdResult<WhitelistStatusResponse> {
+        let req = QueryMsg::WhitelistStatus {
+            address: address.into(),
+            proof,
+            voucher,
+        };
+        self.query(querier, req)
+    }
+}
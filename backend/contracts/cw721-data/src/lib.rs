@@ -1,14 +1,30 @@
 mod contract_tests;
 mod error;
 mod execute;
+pub mod galaxy;
+pub mod merkle;
 pub mod msg;
 mod query;
 pub mod state;
-mod extension;
+pub mod voucher;
+pub mod extension;
+#[cfg(any(test, feature = "library"))]
+pub mod helpers;
 
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, AdminResponse, QueryMsg};
-pub use crate::state::Cw721Contract;
+pub use crate::extension::{
+    NftDataResponse, NftsDataPageResponse, NftsDataResponse, SystemPlanetsResponse,
+};
+#[cfg(any(test, feature = "library"))]
+pub use crate::helpers::Cw721DataContract;
+pub use crate::msg::{
+    AdminResponse, BalanceResponse, ExecuteMsg, Holder, HoldersResponse, HooksResponse,
+    InstantiateMsg, MigrateMsg, MintMsg, OperatorGrant, OperatorGrantsResponse, PhaseWhitelist,
+    PlanetLocationResponse, QueryMsg, RecentEdit, RecentEditsResponse, SalePhase,
+    SalePhaseStatus, SalePhasesResponse, SpenderApproval, SpenderApprovalsResponse, UpdateMsg,
+    WhitelistStatusResponse, WhitelistVoucher,
+};
+pub use crate::state::{Cw721Contract, DataFormat, StorageKeys, STORAGE_KEYS};
 use cosmwasm_std::Empty;
 
 // This is a simple type to let us handle empty extensions
@@ -19,7 +35,7 @@ pub mod entry {
     use super::*;
 
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult};
 
     // This makes a conscious choice on the various generics used by the contract
     #[entry_point]
@@ -49,4 +65,16 @@ pub mod entry {
         let tract = Cw721Contract::<Extension, Empty>::default();
         tract.query(deps, env, msg)
     }
+
+    #[entry_point]
+    pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty>::default();
+        tract.reply(deps, env, msg)
+    }
+
+    #[entry_point]
+    pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty>::default();
+        tract.migrate(deps, env, msg)
+    }
 }
@@ -0,0 +1,107 @@
+use cosmwasm_std::{StdError, StdResult};
+use sha2::Digest;
+use std::convert::TryInto;
+
+use crate::error::ContractError;
+
+/// Whitelist tree built off-chain, hashed the same way `verify_proof` checks it.
+/// A node without a sibling is moved up to the next layer unchanged
+pub struct MerkleTree {
+    /// Leaves first, the root is the only node of the last layer
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(addresses: &[String]) -> StdResult<Self> {
+        if addresses.is_empty() {
+            return Err(StdError::generic_err("Empty address list"));
+        }
+        let mut layers = vec![addresses
+            .iter()
+            .map(|address| leaf_hash(address))
+            .collect::<Vec<_>>()];
+        while layers[layers.len() - 1].len() > 1 {
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(*a, *b),
+                    _ => pair[0],
+                })
+                .collect();
+            layers.push(next);
+        }
+        Ok(MerkleTree { layers })
+    }
+
+    /// Hex encoded root, as expected by `InstantiateMsg::merkle_root`
+    pub fn root(&self) -> String {
+        hex::encode(self.layers[self.layers.len() - 1][0])
+    }
+
+    /// Hex encoded proof of the address at `index` in the list the tree was built from
+    pub fn proof(&self, mut index: usize) -> Vec<String> {
+        let mut proof = vec![];
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < layer.len() {
+                proof.push(hex::encode(layer[sibling]));
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
+
+/// Leaf of the whitelist tree, the sha256 of the bech32 address
+pub fn leaf_hash(address: &str) -> [u8; 32] {
+    sha2::Sha256::digest(address.as_bytes())
+        .as_slice()
+        .try_into()
+        .expect("Wrong length")
+}
+
+/// Parent node of two nodes, the smaller hash goes first so proofs do not need a side
+pub fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+    let pair = if bytes_cmp(a, b) == std::cmp::Ordering::Less {
+        [a, b].concat()
+    } else {
+        [b, a].concat()
+    };
+    sha2::Sha256::digest(&pair)
+        .as_slice()
+        .try_into()
+        .expect("Wrong length")
+}
+
+/// Checks the hex encoded proof of `address` against the hex encoded merkle root
+pub fn verify_proof(
+    merkle_root: &str,
+    address: &str,
+    proof: &[String],
+) -> Result<bool, ContractError> {
+    let mut hash = leaf_hash(address);
+    for p in proof {
+        let mut proof_buf: [u8; 32] = [0; 32];
+        match hex::decode_to_slice(p, &mut proof_buf) {
+            Ok(()) => {}
+            _ => return Err(ContractError::InvalidHexProof {}),
+        }
+        hash = hash_pair(hash, proof_buf);
+    }
+
+    let mut root_buf: [u8; 32] = [0; 32];
+    Ok(hex::decode_to_slice(merkle_root, &mut root_buf).is_ok() && root_buf == hash)
+}
+
+fn bytes_cmp(a: [u8; 32], b: [u8; 32]) -> std::cmp::Ordering {
+    let mut i = 0;
+    while i < 32 {
+        match a[i].cmp(&b[i]) {
+            std::cmp::Ordering::Greater => return std::cmp::Ordering::Greater,
+            std::cmp::Ordering::Less => return std::cmp::Ordering::Less,
+            _ => i += 1,
+        }
+    }
+
+    std::cmp::Ordering::Equal
+}
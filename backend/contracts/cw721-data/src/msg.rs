@@ -1,9 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Binary};
+use cosmwasm_std::{Binary, Timestamp};
 use cw721::Expiration;
-use terraswap::asset::{Asset};
+use terraswap::asset::Asset;
+
+use crate::state::DataFormat;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -24,6 +26,9 @@ pub struct InstantiateMsg {
 
     pub merkle_root: String,
 
+    /// secp256k1 public key signing whitelist vouchers, accepted alongside the merkle proofs
+    pub whitelist_signer: Option<Binary>,
+
     // Mint Denom
     pub denom: String,
 
@@ -33,6 +38,16 @@ pub struct InstantiateMsg {
     pub max_issuance: u64,
 
     pub token_uri: String,
+
+    /// Shuffle the order token ids are minted in, drawing each id from the unminted pool.
+    /// This is not sniper protection: the minter can predict the draw, see the README
+    pub random_mint: Option<bool>,
+
+    /// Website base url used to build the external and animation urls of the planets
+    pub website_url: Option<String>,
+
+    /// Ordered sale phases replacing the whitelist and open mint times, see `UpdateSalePhases`
+    pub sale_phases: Option<Vec<SalePhase>>,
 }
 
 /// This is like Cw721ExecuteMsg but the mint is changed to allow anyone to mint during our minting periods
@@ -73,11 +88,56 @@ pub enum ExecuteMsg<T> {
     /// Update data on the contract, can only be called by the owner 
     Update(UpdateMsg),
 
+    /// Set the name and description of a planet, can only be called by the owner.
+    /// Unset fields clear the stored value, the profile stays with the planet on transfer
+    SetProfile {
+        token_id: String,
+        name: Option<String>,
+        description: Option<String>,
+    },
+
     // Withdraw balance from the contract
     Withdraw { denom: String},
 
     // Update the mint price of the NFT Token
-    UpdatePrice { price: u64 }
+    UpdatePrice { price: u64 },
+
+    // Update the website linked from the planet metadata, unset removes the links
+    UpdateWebsiteUrl { website_url: Option<String> },
+
+    /// Register a contract to receive a `PlanetHookMsg` on every mint, transfer and edit.
+    /// Admin only, a failing hook is logged with a `hook_failed` event and does not block the change
+    AddHook { addr: String },
+
+    /// Unregister a hook contract, admin only
+    RemoveHook { addr: String },
+
+    /// Delete the expired grants among the next `limit` operator grants of `owner` after
+    /// `operator_start_after`, and the expired approvals of its next `limit` tokens after
+    /// `start_after`. Anyone can call it, the `next_operator_start_after` and
+    /// `next_start_after` attributes are the cursors of the next batch, empty once done
+    PruneExpired {
+        owner: String,
+        operator_start_after: Option<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Move the next `limit` tokens still stored under their id string to their numeric key
+    /// after a migration from storage layout 0, admin only. Repeat with `next_start_after` until
+    /// it comes back empty
+    MigrateTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Set the public key signing whitelist vouchers, unset stops accepting vouchers. Admin only
+    UpdateWhitelistSigner { public_key: Option<Binary> },
+
+    /// Replace the sale phases, admin only. Phases must be in start order without overlapping
+    /// and have unique names, the usage of a phase is kept while its name is unchanged.
+    /// An empty list goes back to the whitelist and open mint times
+    UpdateSalePhases { phases: Vec<SalePhase> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -96,10 +156,30 @@ pub struct MintMsg<T> {
     //Offer Asset
     pub offer_asset: Asset,
 
+    /// Whitelist voucher signed for the sender, an alternative to the merkle proof
+    pub voucher: Option<WhitelistVoucher>,
+
+    /// Sender chosen salt mixed into the id draw when `random_mint` is set. It is not a
+    /// commitment, the sender knows the drawn id before the mint lands
+    pub salt: Option<String>,
+
     /// Any custom extension used by this contract
     pub extension: T,
 }
 
+/// Permission to mint `allocation` planets during the whitelist mint, signed off-chain
+/// by the whitelist signer for one contract, one minter and one sale phase
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WhitelistVoucher {
+    /// Whitelist mints the minter may use in total, over the whitelist mint and every
+    /// voucher phase
+    pub allocation: u64,
+    /// Unix timestamp in seconds after which the voucher is refused
+    pub expires: u64,
+    /// Compact secp256k1 signature (64 bytes) of the voucher
+    pub signature: Binary,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -159,10 +239,168 @@ pub enum QueryMsg {
     // Return the minter
     Admin {},
 
-    /// Return nft data for a list of nfts
+    /// Return nft data for a list of nfts, in the order of the list
     NftsData {
         token_ids: Vec<String>,
     },
+
+    /// Pages through the nft data of the whole collection
+    /// Return type: NftsDataPageResponse
+    AllNftsData {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Pages through the nft data of the tokens owned by the given address
+    /// Return type: NftsDataPageResponse
+    OwnerNftsData {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Place of a planet in the galaxy, also works for planets that are not minted yet
+    /// Return type: PlanetLocationResponse
+    PlanetLocation {
+        token_id: String,
+    },
+
+    /// Return nft data for every minted planet of a star system
+    /// Return type: SystemPlanetsResponse
+    SystemPlanets {
+        system_index: u64,
+    },
+
+    /// Latest planet edits, newest first. Only the last 100 edits are kept
+    /// Return type: RecentEditsResponse
+    RecentEdits {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    /// Lists the planets stored in the given data format
+    /// Return type: TokensResponse.
+    TokensByFormat {
+        format: DataFormat,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Number of planets stored in the given data format
+    /// Return type: NumTokensResponse
+    NumTokensByFormat {
+        format: DataFormat,
+    },
+
+    /// Number of tokens held by the given address
+    /// Return type: BalanceResponse
+    Balance {
+        owner: String,
+    },
+
+    /// Lists every address holding at least one token, with its token count
+    /// Return type: HoldersResponse
+    Holders {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Checks a whitelist proof or voucher the same way `Mint` does, without minting. With
+    /// sale phases the proof, the voucher and the caps are those of the active phase
+    /// Return type: WhitelistStatusResponse
+    WhitelistStatus {
+        address: String,
+        proof: Vec<String>,
+        /// checked instead of the proof when set
+        voucher: Option<WhitelistVoucher>,
+    },
+
+    /// Contracts notified of planet mints, transfers and edits
+    /// Return type: HooksResponse
+    Hooks {},
+
+    /// Lists the owners who made the given address an operator with `ApproveAll`
+    /// Return type: OperatorGrantsResponse
+    OperatorGrants {
+        operator: String,
+        /// unset or false will filter out expired grants, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists the tokens the given address is approved for with `Approve`
+    /// Return type: SpenderApprovalsResponse
+    SpenderApprovals {
+        spender: String,
+        /// unset or false will filter out expired approvals, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Lists the sale phases with the planets minted in each, and by `address` when given
+    /// Return type: SalePhasesResponse
+    SalePhases {
+        address: Option<String>,
+    },
+}
+
+/// A period of the sale with its own price and limits, phases follow each other without overlapping
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SalePhase {
+    /// Unique name of the phase, its usage is tracked by name. The name of a removed phase
+    /// that had mints cannot be used again
+    pub name: String,
+    /// Unix timestamp in seconds the phase starts at
+    pub start_time: u64,
+    /// Unix timestamp in seconds the phase ends at, unset runs until the supply is minted
+    pub end_time: Option<u64>,
+    /// Who may mint during the phase
+    pub whitelist: PhaseWhitelist,
+    /// Mint price in the contract denom
+    pub price: u64,
+    /// Mints allowed per address during the phase, required with a merkle whitelist
+    pub wallet_cap: Option<u64>,
+    /// Mints allowed during the phase
+    pub supply_cap: Option<u64>,
+}
+
+/// Whitelist of a sale phase
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseWhitelist {
+    /// Anyone can mint
+    None,
+    /// Addresses with a proof against the hex encoded merkle root can mint
+    Merkle { root: String },
+    /// Minters with a voucher signed for the phase can mint up to its allocation
+    Voucher,
+}
+
+impl SalePhase {
+    pub fn is_active(&self, time: Timestamp) -> bool {
+        let ended =
+            matches!(self.end_time, Some(end_time) if time >= Timestamp::from_seconds(end_time));
+        time >= Timestamp::from_seconds(self.start_time) && !ended
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SalePhaseStatus {
+    pub phase: SalePhase,
+    /// The phase is the one `Mint` uses right now
+    pub active: bool,
+    /// Planets minted during the phase
+    pub minted: u64,
+    /// Planets the queried address minted during the phase, unset when no address is given
+    pub minted_by_address: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SalePhasesResponse {
+    /// In sale order
+    pub phases: Vec<SalePhaseStatus>,
 }
 
 /// Shows who can mint these tokens
@@ -171,5 +409,102 @@ pub struct AdminResponse {
     pub admin: String,
 }
 
+/// Contracts notified of planet mints, transfers and edits, in registration order
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HooksResponse {
+    pub hooks: Vec<String>,
+}
+
+/// An owner whose tokens the operator can transfer
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OperatorGrant {
+    pub owner: String,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OperatorGrantsResponse {
+    /// Ordered by owner address, use the last one as `start_after` to get the next page
+    pub grants: Vec<OperatorGrant>,
+}
+
+/// A token the spender is approved for
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SpenderApproval {
+    pub token_id: String,
+    pub owner: String,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SpenderApprovalsResponse {
+    /// Ordered by token id, use the last one as `start_after` to get the next page
+    pub approvals: Vec<SpenderApproval>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PlanetLocationResponse {
+    pub token_id: String,
+    /// Galaxy arm of the star system, from 0 to 3
+    pub arm: u64,
+    /// Star system id, starting at 1 like the `/system/:id` page of the website
+    pub system_index: u64,
+    pub system_name: String,
+    /// Slot of the planet in its system, 0 is the orbit closest to the star
+    pub position: u64,
+    pub star_color: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RecentEdit {
+    /// Sequence number of the edit, use it as `start_after` to get the next page
+    pub edit_id: u64,
+    pub token_id: String,
+    pub editor: String,
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RecentEditsResponse {
+    pub edits: Vec<RecentEdit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BalanceResponse {
+    pub balance: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Holder {
+    pub address: String,
+    /// Number of tokens held by the address
+    pub tokens: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HoldersResponse {
+    pub holders: Vec<Holder>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct WhitelistStatusResponse {
+    /// Name of the active sale phase, unset when no sale phases are configured
+    pub phase: Option<String>,
+    /// Hex encoded merkle root the proof is checked against, unset when the active phase has
+    /// no merkle whitelist
+    pub merkle_root: Option<String>,
+    /// The address may mint: the proof places it in the merkle tree, the voucher is good or
+    /// the phase is public
+    pub valid: bool,
+    /// The address used up its mints
+    pub claimed: bool,
+    /// Planets the address minted, in the active phase when phases are configured
+    pub minted: u64,
+    /// Wallet cap of the active phase, or without phases 1 or the voucher allocation
+    pub cap: Option<u64>,
+    /// Mints the address has left, unset when nothing limits it
+    pub remaining: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {}
\ No newline at end of file
@@ -6,13 +6,28 @@ use cosmwasm_std::{to_binary, Binary, BlockInfo, Deps, Env, Order, Pair, StdErro
 use cw0::maybe_addr;
 use cw721::{
     AllNftInfoResponse, ApprovedForAllResponse, ContractInfoResponse, CustomMsg, Cw721Query,
-    Expiration, NftInfoResponse, NumTokensResponse, OwnerOfResponse, TokensResponse, NftDataExtension,
+    Expiration, NftInfoResponse, NumTokensResponse, OwnerOfResponse, TokensResponse, NftDataExtension, Trait,
 };
-use cw_storage_plus::Bound;
+use cw_storage_plus::{Bound, U64Key};
+use std::convert::TryInto;
 
-use crate::msg::{AdminResponse, QueryMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
-use crate::extension::{NftsDataResponse, NftDataResponse};
+use crate::error::ContractError;
+use crate::galaxy;
+use crate::merkle;
+use crate::voucher;
+use crate::msg::{
+    AdminResponse, BalanceResponse, Holder, HoldersResponse, HooksResponse, OperatorGrant,
+    OperatorGrantsResponse, SpenderApproval, SpenderApprovalsResponse, PlanetLocationResponse, QueryMsg,
+    RecentEdit, RecentEditsResponse, SalePhaseStatus, SalePhasesResponse, WhitelistStatusResponse,
+    PhaseWhitelist, WhitelistVoucher,
+};
+use crate::state::{
+    token_bound, token_id_from_key, token_key, Approval, Cw721Contract, DataFormat, EditInfo,
+    PlanetProfile, TokenInfo,
+};
+use crate::extension::{
+    NftDataResponse, NftsDataPageResponse, NftsDataResponse, SystemPlanetsResponse,
+};
 
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_LIMIT: u32 = 30;
@@ -31,11 +46,11 @@ where
         Ok(NumTokensResponse { count })
     }
 
-    fn nft_info(&self, deps: Deps, token_id: String) -> StdResult<NftInfoResponse> {
-        let info = self.tokens.load(deps.storage, &token_id)?;
+    fn nft_info(&self, deps: Deps, token_id: String) -> StdResult<NftInfoResponse<T>> {
+        let info = self.tokens.load(deps.storage, token_key(&token_id)?)?;
         Ok(NftInfoResponse {
             image: info.token_uri.clone(),
-            extension: create_extension(info.token_uri, token_id)
+            extension: self.planet_metadata(deps, &token_id, &info)?,
         })
     }
 
@@ -46,7 +61,7 @@ where
         token_id: String,
         include_expired: bool,
     ) -> StdResult<OwnerOfResponse> {
-        let info = self.tokens.load(deps.storage, &token_id)?;
+        let info = self.tokens.load(deps.storage, token_key(&token_id)?)?;
         Ok(OwnerOfResponse {
             owner: info.owner.to_string(),
             approvals: humanize_approvals(&env.block, &info, include_expired),
@@ -88,7 +103,7 @@ where
         limit: Option<u32>,
     ) -> StdResult<TokensResponse> {
         let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-        let start = start_after.map(Bound::exclusive);
+        let start = token_bound(start_after)?;
 
         let owner_addr = deps.api.addr_validate(&owner)?;
         let pks: Vec<_> = self
@@ -100,8 +115,8 @@ where
             .take(limit)
             .collect();
 
-        let res: Result<Vec<_>, _> = pks.iter().map(|v| String::from_utf8(v.to_vec())).collect();
-        let tokens = res.map_err(StdError::invalid_utf8)?;
+        let tokens: StdResult<Vec<_>> = pks.iter().map(|pk| token_id_from_key(pk)).collect();
+        let tokens = tokens?;
         Ok(TokensResponse { tokens })
     }
 
@@ -112,13 +127,13 @@ where
         limit: Option<u32>,
     ) -> StdResult<TokensResponse> {
         let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-        let start = start_after.map(Bound::exclusive);
+        let start = token_bound(start_after)?;
 
         let tokens: StdResult<Vec<String>> = self
             .tokens
             .range(deps.storage, start, None, Order::Ascending)
             .take(limit)
-            .map(|item| item.map(|(k, _)| String::from_utf8_lossy(&k).to_string()))
+            .map(|item| item.and_then(|(k, _)| token_id_from_key(&k)))
             .collect();
         Ok(TokensResponse { tokens: tokens? })
     }
@@ -129,9 +144,8 @@ where
         env: Env,
         token_id: String,
         include_expired: bool,
-    ) -> StdResult<AllNftInfoResponse> {
-        
-        let info = self.tokens.load(deps.storage, &token_id)?;
+    ) -> StdResult<AllNftInfoResponse<T>> {
+        let info = self.tokens.load(deps.storage, token_key(&token_id)?)?;
         Ok(AllNftInfoResponse {
             access: OwnerOfResponse {
                 owner: info.owner.to_string(),
@@ -139,7 +153,7 @@ where
             },
             info: NftInfoResponse {
                 image: info.token_uri.clone(),
-                extension: create_extension(info.token_uri, token_id),
+                extension: self.planet_metadata(deps, &token_id, &info)?,
             },
         })
     }
@@ -157,6 +171,120 @@ where
         })
     }
 
+    pub fn hooks(&self, deps: Deps) -> StdResult<HooksResponse> {
+        let hooks = self.hooks.may_load(deps.storage)?.unwrap_or_default();
+        Ok(HooksResponse {
+            hooks: hooks.into_iter().map(String::from).collect(),
+        })
+    }
+
+    pub fn operator_grants(
+        &self,
+        deps: Deps,
+        env: Env,
+        operator: String,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OperatorGrantsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
+
+        let operator_addr = deps.api.addr_validate(&operator)?;
+        let grants: StdResult<Vec<_>> = self
+            .operator_grants
+            .prefix(&operator_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|r| {
+                include_expired || r.is_err() || !r.as_ref().unwrap().1.is_expired(&env.block)
+            })
+            .take(limit)
+            .map(|item| {
+                let (owner, expires) = item?;
+                let owner = String::from_utf8(owner)?;
+                Ok(OperatorGrant { owner, expires })
+            })
+            .collect();
+        Ok(OperatorGrantsResponse { grants: grants? })
+    }
+
+    pub fn spender_approvals(
+        &self,
+        deps: Deps,
+        env: Env,
+        spender: String,
+        include_expired: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<SpenderApprovalsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = token_bound(start_after)?;
+
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        let approvals: StdResult<Vec<_>> = self
+            .spender_approvals
+            .prefix(&spender_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|r| {
+                include_expired || r.is_err() || !r.as_ref().unwrap().1.is_expired(&env.block)
+            })
+            .take(limit)
+            .map(|item| {
+                let (key, expires) = item?;
+                let token = self.tokens.load(deps.storage, U64Key::from(key.clone()))?;
+                Ok(SpenderApproval {
+                    token_id: token_id_from_key(&key)?,
+                    owner: token.owner.to_string(),
+                    expires,
+                })
+            })
+            .collect();
+        Ok(SpenderApprovalsResponse {
+            approvals: approvals?,
+        })
+    }
+
+    pub fn sale_phases(
+        &self,
+        deps: Deps,
+        env: Env,
+        address: Option<String>,
+    ) -> StdResult<SalePhasesResponse> {
+        let addr = maybe_addr(deps.api, address)?;
+        let phases = self.sale_phases.may_load(deps.storage)?.unwrap_or_default();
+        // mint uses the first active phase
+        let active = phases
+            .iter()
+            .position(|phase| phase.is_active(env.block.time));
+
+        let phases: StdResult<Vec<_>> = phases
+            .into_iter()
+            .enumerate()
+            .map(|(index, phase)| {
+                let minted = self
+                    .phase_minted
+                    .may_load(deps.storage, &phase.name)?
+                    .unwrap_or_default();
+                let minted_by_address = match &addr {
+                    Some(addr) => Some(
+                        self.phase_claims
+                            .may_load(deps.storage, (&phase.name, addr))?
+                            .unwrap_or_default(),
+                    ),
+                    None => None,
+                };
+                Ok(SalePhaseStatus {
+                    phase,
+                    active: active == Some(index),
+                    minted,
+                    minted_by_address,
+                })
+            })
+            .collect();
+        Ok(SalePhasesResponse { phases: phases? })
+    }
+
     pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         match msg {
             QueryMsg::Admin {} => to_binary(&self.admin(deps)?),
@@ -184,6 +312,14 @@ where
                 env,
                 token_ids,
             )?),
+            QueryMsg::AllNftsData { start_after, limit } => {
+                to_binary(&self.all_nfts_data(deps, env, start_after, limit)?)
+            }
+            QueryMsg::OwnerNftsData {
+                owner,
+                start_after,
+                limit,
+            } => to_binary(&self.owner_nfts_data(deps, env, owner, start_after, limit)?),
             QueryMsg::ApprovedForAll {
                 owner,
                 include_expired,
@@ -206,40 +342,433 @@ where
             QueryMsg::AllTokens { start_after, limit } => {
                 to_binary(&self.all_tokens(deps, start_after, limit)?)
             }
+            QueryMsg::PlanetLocation { token_id } => {
+                to_binary(&self.planet_location(deps, token_id)?)
+            }
+            QueryMsg::SystemPlanets { system_index } => {
+                to_binary(&self.system_planets(deps, env, system_index)?)
+            }
+            QueryMsg::RecentEdits { start_after, limit } => {
+                to_binary(&self.recent_edits(deps, start_after, limit)?)
+            }
+            QueryMsg::TokensByFormat {
+                format,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_by_format(deps, format, start_after, limit)?),
+            QueryMsg::NumTokensByFormat { format } => {
+                to_binary(&self.num_tokens_by_format(deps, format)?)
+            }
+            QueryMsg::Balance { owner } => to_binary(&self.balance_of(deps, owner)?),
+            QueryMsg::Holders { start_after, limit } => {
+                to_binary(&self.holders(deps, start_after, limit)?)
+            }
+            QueryMsg::WhitelistStatus {
+                address,
+                proof,
+                voucher,
+            } => to_binary(&self.whitelist_status(deps, env, address, proof, voucher)?),
+            QueryMsg::Hooks {} => to_binary(&self.hooks(deps)?),
+            QueryMsg::OperatorGrants {
+                operator,
+                include_expired,
+                start_after,
+                limit,
+            } => to_binary(&self.operator_grants(
+                deps,
+                env,
+                operator,
+                include_expired.unwrap_or(false),
+                start_after,
+                limit,
+            )?),
+            QueryMsg::SpenderApprovals {
+                spender,
+                include_expired,
+                start_after,
+                limit,
+            } => to_binary(&self.spender_approvals(
+                deps,
+                env,
+                spender,
+                include_expired.unwrap_or(false),
+                start_after,
+                limit,
+            )?),
+            QueryMsg::SalePhases { address } => to_binary(&self.sale_phases(deps, env, address)?),
         }
     }
+
+    pub fn planet_location(&self, deps: Deps, token_id: String) -> StdResult<PlanetLocationResponse> {
+        let max_issuance = self.max_issuance.load(deps.storage)?;
+        match parse_planet_id(&token_id, max_issuance) {
+            Some(planet_id) => Ok(locate_planet(token_id, planet_id)),
+            None => Err(StdError::generic_err("Invalid planet id")),
+        }
+    }
+
     fn nfts_data(
         &self,
         deps: Deps,
         env: Env,
-        mut token_ids: Vec<String>,
-    ) -> StdResult<NftsDataResponse> {
+        token_ids: Vec<String>,
+    ) -> StdResult<NftsDataResponse<T>> {
         if token_ids.len() > 10 {
             return Err(StdError::GenericErr { msg: "Query Limit of 10 NFTs".to_string()});
         } 
-        //Sort and remove duplicates from token_ids
-        token_ids.sort();
-        token_ids.dedup();
-        let mut nfts: Vec<NftDataResponse> = Vec::new();
+        //Answer in the requested order, skipping repeated ids
+        let mut nfts: Vec<NftDataResponse<T>> = Vec::new();
+        let mut missing: Vec<String> = Vec::new();
+        for (index, token_id) in token_ids.iter().enumerate() {
+            if token_ids[..index].contains(token_id) {
+                continue;
+            }
+            match self.nft_data(deps, &env, token_id)? {
+                Some(nft) => nfts.push(nft),
+                None => missing.push(token_id.clone()),
+            }
+        }
+        Ok(NftsDataResponse {
+            nfts: Some(nfts),
+            missing,
+        })
+    }
+
+    pub fn all_nfts_data(
+        &self,
+        deps: Deps,
+        env: Env,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<NftsDataPageResponse<T>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = token_bound(start_after)?;
+
+        let tokens: StdResult<Vec<_>> = self
+            .tokens
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect();
+        self.nfts_data_page(deps, &env, tokens?, limit)
+    }
+
+    pub fn owner_nfts_data(
+        &self,
+        deps: Deps,
+        env: Env,
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<NftsDataPageResponse<T>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = token_bound(start_after)?;
+
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let tokens: StdResult<Vec<_>> = self
+            .tokens
+            .idx
+            .owner
+            .prefix(owner_addr)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect();
+        self.nfts_data_page(deps, &env, tokens?, limit)
+    }
+
+    /// Turns a page of stored tokens into nft data, a full page gets a cursor to the next one
+    fn nfts_data_page(
+        &self,
+        deps: Deps,
+        env: &Env,
+        tokens: Vec<Pair<TokenInfo<T>>>,
+        limit: usize,
+    ) -> StdResult<NftsDataPageResponse<T>> {
+        let full_page = tokens.len() == limit;
+        let mut nfts: Vec<NftDataResponse<T>> = Vec::new();
+        for (key, info) in tokens {
+            let token_id = token_id_from_key(&key)?;
+            nfts.push(self.token_data(deps, env, &token_id, info)?);
+        }
+        let next_start_after = if full_page {
+            nfts.last().and_then(|nft| nft.token_id.clone())
+        } else {
+            None
+        };
+        Ok(NftsDataPageResponse {
+            nfts,
+            next_start_after,
+        })
+    }
+
+    pub fn system_planets(
+        &self,
+        deps: Deps,
+        env: Env,
+        system_index: u64,
+    ) -> StdResult<SystemPlanetsResponse<T>> {
+        let max_issuance = self.max_issuance.load(deps.storage)?;
+        if system_index < 1 || system_index > galaxy::total_systems(max_issuance) {
+            return Err(StdError::generic_err("Invalid system index"));
+        }
+
+        let token_ids: Vec<String> = galaxy::system_planets(system_index, max_issuance)
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        let mut nfts: Vec<NftDataResponse<T>> = Vec::new();
         for token_id in token_ids.iter() {
-            let info = self.tokens.may_load(deps.storage, &token_id)?;
-            
-            match info {
-                Some (p) => nfts.push(
-                    NftDataResponse {
-                        access: OwnerOfResponse {
-                            owner: p.owner.to_string(),
-                            approvals: humanize_approvals(&env.block, &p, false),
-                        },
-                        token_id: Some(token_id.to_string()),
-                        token_uri: p.token_uri.clone(),
-                        data: p.data,
-                        extension: create_extension(p.token_uri.clone(),token_id.to_string()),
-                    }),
-                None => (),
+            if let Some(nft) = self.nft_data(deps, &env, token_id)? {
+                nfts.push(nft);
             }
         }
-        Ok(NftsDataResponse { nfts: Some(nfts) })
+        Ok(SystemPlanetsResponse {
+            system_index,
+            token_ids,
+            nfts,
+        })
+    }
+
+    pub fn tokens_by_format(
+        &self,
+        deps: Deps,
+        format: DataFormat,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = token_bound(start_after)?;
+
+        let pks: Vec<_> = self
+            .tokens
+            .idx
+            .format
+            .prefix(format.as_str().as_bytes().to_vec())
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect();
+
+        let tokens: StdResult<Vec<_>> = pks.iter().map(|pk| token_id_from_key(pk)).collect();
+        let tokens = tokens?;
+        Ok(TokensResponse { tokens })
+    }
+
+    pub fn num_tokens_by_format(&self, deps: Deps, format: DataFormat) -> StdResult<NumTokensResponse> {
+        let count = self.format_count(deps.storage, format)?;
+        Ok(NumTokensResponse { count })
+    }
+
+    pub fn balance_of(&self, deps: Deps, owner: String) -> StdResult<BalanceResponse> {
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let balance = self.balance(deps.storage, &owner_addr)?;
+        Ok(BalanceResponse { balance })
+    }
+
+    pub fn holders(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<HoldersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.map(|addr| Bound::exclusive(addr.as_ref()));
+
+        let holders: StdResult<Vec<_>> = self
+            .balances
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(parse_holder)
+            .collect();
+        Ok(HoldersResponse { holders: holders? })
+    }
+
+    pub fn whitelist_status(
+        &self,
+        deps: Deps,
+        env: Env,
+        address: String,
+        proof: Vec<String>,
+        voucher: Option<WhitelistVoucher>,
+    ) -> StdResult<WhitelistStatusResponse> {
+        let addr = deps.api.addr_validate(&address)?;
+        let verify = |merkle_root: &str| {
+            merkle::verify_proof(merkle_root, addr.as_str(), &proof)
+                .map_err(|err| StdError::generic_err(err.to_string()))
+        };
+        //a voucher that mint would refuse is reported as invalid
+        let signer = self.whitelist_signer.may_load(deps.storage)?;
+        let voucher_valid = |phase: &str, voucher: &WhitelistVoucher| match &signer {
+            Some(signer) => {
+                voucher::check_voucher(deps.api, &env, signer, &addr, phase, voucher).is_ok()
+            }
+            None => false,
+        };
+
+        //Without sale phases every whitelisted address can mint once, or its voucher allocation
+        let phases = self.sale_phases.may_load(deps.storage)?.unwrap_or_default();
+        if phases.is_empty() {
+            let merkle_root = self.merkle_root.load(deps.storage)?;
+            let (valid, cap) = match &voucher {
+                Some(voucher) => (voucher_valid("", voucher), voucher.allocation),
+                None => (verify(&merkle_root)?, 1),
+            };
+            let minted = self.claimed.may_load(deps.storage, &addr)?.unwrap_or_default();
+            let remaining = if valid { cap.saturating_sub(minted) } else { 0 };
+            return Ok(WhitelistStatusResponse {
+                phase: None,
+                merkle_root: Some(merkle_root),
+                valid,
+                claimed: minted >= cap,
+                minted,
+                cap: Some(cap),
+                remaining: Some(remaining),
+            });
+        }
+
+        //Otherwise the active phase sets the root and the caps, like in mint
+        let phase = phases
+            .into_iter()
+            .find(|phase| phase.is_active(env.block.time))
+            .ok_or_else(|| StdError::generic_err(ContractError::SaleNotActive {}.to_string()))?;
+        let (valid, allocation_left) = match (&phase.whitelist, &voucher) {
+            (PhaseWhitelist::None, _) => (true, None),
+            (PhaseWhitelist::Merkle { root }, _) => (verify(root)?, None),
+            (PhaseWhitelist::Voucher, Some(voucher)) => {
+                let used = self.claimed.may_load(deps.storage, &addr)?.unwrap_or_default();
+                let allocation_left = voucher.allocation.saturating_sub(used);
+                (voucher_valid(&phase.name, voucher), Some(allocation_left))
+            }
+            (PhaseWhitelist::Voucher, None) => (false, None),
+        };
+        let minted = self
+            .phase_claims
+            .may_load(deps.storage, (&phase.name, &addr))?
+            .unwrap_or_default();
+        let phase_minted = self
+            .phase_minted
+            .may_load(deps.storage, &phase.name)?
+            .unwrap_or_default();
+        let wallet_left = phase.wallet_cap.map(|cap| cap.saturating_sub(minted));
+        let wallet_left = min_cap(wallet_left, allocation_left);
+        let supply_left = phase.supply_cap.map(|cap| cap.saturating_sub(phase_minted));
+        let remaining = if valid {
+            min_cap(wallet_left, supply_left)
+        } else {
+            Some(0)
+        };
+        Ok(WhitelistStatusResponse {
+            phase: Some(phase.name),
+            merkle_root: match phase.whitelist {
+                PhaseWhitelist::Merkle { root } => Some(root),
+                _ => None,
+            },
+            valid,
+            claimed: wallet_left == Some(0),
+            minted,
+            cap: phase.wallet_cap,
+            remaining,
+        })
+    }
+
+    pub fn recent_edits(
+        &self,
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<RecentEditsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let end = start_after.map(Bound::exclusive_int);
+
+        let edits: StdResult<Vec<RecentEdit>> = self
+            .recent_edits
+            .range(deps.storage, None, end, Order::Descending)
+            .take(limit)
+            .map(parse_edit)
+            .collect();
+        Ok(RecentEditsResponse { edits: edits? })
+    }
+
+    /// Data, ownership and metadata of a planet, None if it is not minted
+    fn nft_data(
+        &self,
+        deps: Deps,
+        env: &Env,
+        token_id: &str,
+    ) -> StdResult<Option<NftDataResponse<T>>> {
+        // ids that are not valid token ids cannot be minted
+        let key = match token_key(token_id) {
+            Ok(key) => key,
+            Err(_) => return Ok(None),
+        };
+        match self.tokens.may_load(deps.storage, key)? {
+            Some(info) => self.token_data(deps, env, token_id, info).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn token_data(
+        &self,
+        deps: Deps,
+        env: &Env,
+        token_id: &str,
+        info: TokenInfo<T>,
+    ) -> StdResult<NftDataResponse<T>> {
+        let extension = self.planet_metadata(deps, token_id, &info)?;
+        Ok(NftDataResponse {
+            access: OwnerOfResponse {
+                owner: info.owner.to_string(),
+                approvals: humanize_approvals(&env.block, &info, false),
+            },
+            token_id: Some(token_id.to_string()),
+            token_uri: info.token_uri,
+            data: info.data,
+            extension,
+        })
+    }
+
+    /// Builds the on-chain metadata of a planet, the owner profile replaces the default
+    /// name and the website links are only set once the admin configured a website url
+    fn planet_metadata(
+        &self,
+        deps: Deps,
+        token_id: &str,
+        info: &TokenInfo<T>,
+    ) -> StdResult<NftDataExtension<T>> {
+        let profile = self
+            .profiles
+            .may_load(deps.storage, token_id)?
+            .unwrap_or(PlanetProfile {
+                name: None,
+                description: None,
+            });
+        let website_url = self.website_url.may_load(deps.storage)?;
+        let max_issuance = self.max_issuance.load(deps.storage)?;
+
+        let mut attributes = vec![format_trait(&info.data)];
+        if let Some(planet_id) = parse_planet_id(token_id, max_issuance) {
+            attributes.extend(location_traits(locate_planet(token_id.to_string(), planet_id)));
+        }
+        Ok(NftDataExtension {
+            image: info.token_uri.clone(),
+            name: profile
+                .name
+                .unwrap_or_else(|| format!("Planet #{}", token_id)),
+            description: profile.description,
+            external_url: website_url
+                .as_ref()
+                .map(|url| format!("{}/planet/{}", url, token_id)),
+            animation_url: website_url.map(|url| format!("{}/explore/{}", url, token_id)),
+            attributes,
+            custom: info.extension.clone(),
+        })
+    }
+}
+
+/// The lower of two optional limits, unset when neither limits
+fn min_cap(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
     }
 }
 
@@ -250,6 +779,28 @@ fn parse_approval(item: StdResult<Pair<Expiration>>) -> StdResult<cw721::Approva
     })
 }
 
+fn parse_holder(item: StdResult<Pair<u64>>) -> StdResult<Holder> {
+    item.and_then(|(k, tokens)| {
+        let address = String::from_utf8(k)?;
+        Ok(Holder { address, tokens })
+    })
+}
+
+fn parse_edit(item: StdResult<Pair<EditInfo>>) -> StdResult<RecentEdit> {
+    item.and_then(|(k, edit)| {
+        let edit_id: [u8; 8] = k
+            .as_slice()
+            .try_into()
+            .map_err(|_| StdError::generic_err("Invalid edit key"))?;
+        Ok(RecentEdit {
+            edit_id: u64::from_be_bytes(edit_id),
+            token_id: edit.token_id,
+            editor: edit.editor.to_string(),
+            time: edit.time,
+        })
+    })
+}
+
 fn humanize_approvals<T>(
     block: &BlockInfo,
     info: &TokenInfo<T>,
@@ -269,12 +820,56 @@ fn humanize_approval(approval: &Approval) -> cw721::Approval {
     }
 }
 
-fn create_extension(
-    image_uri: Option<String>,
-    nft_id: String,
-) -> NftDataExtension {
-    NftDataExtension {
-        image: image_uri,
-        name: format!("Planet #{}", nft_id),
+/// Token ids are the decimal planet ids from 1 to `max_issuance`
+fn parse_planet_id(token_id: &str, max_issuance: u64) -> Option<u64> {
+    match token_id.parse::<u64>() {
+        Ok(id) if id >= 1 && id <= max_issuance => Some(id),
+        _ => None,
+    }
+}
+
+fn locate_planet(token_id: String, planet_id: u64) -> PlanetLocationResponse {
+    let system_index = galaxy::planet_system(planet_id);
+    PlanetLocationResponse {
+        token_id,
+        arm: galaxy::system_arm(system_index),
+        system_index,
+        system_name: galaxy::system_name(system_index),
+        position: galaxy::planet_position(planet_id),
+        star_color: galaxy::system_star_color(system_index).to_string(),
+    }
+}
+
+fn location_traits(location: PlanetLocationResponse) -> Vec<Trait> {
+    let number = |trait_type: &str, value: u64| Trait {
+        display_type: Some("number".to_string()),
+        trait_type: trait_type.to_string(),
+        value: value.to_string(),
+    };
+    let text = |trait_type: &str, value: String| Trait {
+        display_type: None,
+        trait_type: trait_type.to_string(),
+        value,
+    };
+    vec![
+        number("Arm", location.arm),
+        number("System Index", location.system_index),
+        text("System", location.system_name),
+        number("Position", location.position),
+        text("Star Color", location.star_color),
+    ]
+}
+
+fn format_trait(data: &Option<String>) -> Trait {
+    let value = match DataFormat::of(data) {
+        DataFormat::None => "Unedited",
+        DataFormat::Geo1 => "Geodesic",
+        DataFormat::Vox1 => "Voxel",
+        DataFormat::Other => "Unknown",
+    };
+    Trait {
+        display_type: None,
+        trait_type: "Format".to_string(),
+        value: value.to_string(),
     }
 }
@@ -3,10 +3,18 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, BlockInfo, StdResult, Storage, Timestamp, Uint128};
+use cosmwasm_std::{Addr, Binary, BlockInfo, Order, StdError, StdResult, Storage, Timestamp, Uint128};
 
 use cw721::{ContractInfoResponse, CustomMsg, Cw721, Expiration};
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+
+use crate::msg::SalePhase;
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex, U64Key};
+use std::convert::TryInto;
+
+/// Number of edits kept in the recent edits feed
+pub const MAX_RECENT_EDITS: u64 = 100;
+/// Number of hook contracts the admin can register
+pub const MAX_HOOKS: usize = 10;
 
 pub struct Cw721Contract<'a, T, C>
 where
@@ -20,13 +28,48 @@ where
     pub denom: Item<'a, String>,
     pub price: Item<'a, Uint128>,
     pub merkle_root: Item<'a, String>,
+    /// secp256k1 public key signing whitelist vouchers, vouchers are refused while unset
+    pub whitelist_signer: Item<'a, Binary>,
     pub whitelist_mint_time: Item<'a, Timestamp>,
     pub open_mint_time: Item<'a, Timestamp>,
+    pub random_mint: Item<'a, bool>,
+    pub website_url: Item<'a, String>,
+    pub edit_count: Item<'a, u64>,
+    /// Unset on contracts still using `LEGACY_STORAGE_KEYS`
+    pub storage_layout: Item<'a, u64>,
+    /// Contracts notified of mints, transfers and edits, at most `MAX_HOOKS`
+    pub hooks: Item<'a, Vec<Addr>>,
+    /// Ordered sale phases, the whitelist and open mint times apply while unset or empty
+    pub sale_phases: Item<'a, Vec<SalePhase>>,
 
+    /// Sparse swap table of the unminted ids, only written when `random_mint` is set
+    pub id_pool: Map<'a, U64Key, u64>,
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
-    pub claimed: Map<'a, &'a Addr, bool>,
-    pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Reverse of `operators`, stored as (operator, granter)
+    pub operator_grants: Map<'a, (&'a Addr, &'a Addr), Expiration>,
+    /// Token approvals by spender, kept in sync with `TokenInfo.approvals`
+    pub spender_approvals: Map<'a, (&'a Addr, U64Key), Expiration>,
+    /// Whitelist mints used by each address
+    pub claimed: Map<'a, &'a Addr, u64>,
+    /// `claimed` as stored before storage layout 2, a flag per address. Only read by the migration
+    pub legacy_claimed: Map<'a, &'a Addr, bool>,
+    /// Planets minted during each sale phase, keyed by phase name
+    pub phase_minted: Map<'a, &'a str, u64>,
+    /// Planets minted by each address during each sale phase, stored as (phase name, address)
+    pub phase_claims: Map<'a, (&'a str, &'a Addr), u64>,
+    /// Owner chosen name and description, kept apart from the planet data
+    pub profiles: Map<'a, &'a str, PlanetProfile>,
+    /// Latest data updates keyed by edit sequence number, capped at `MAX_RECENT_EDITS`
+    pub recent_edits: Map<'a, U64Key, EditInfo>,
+    /// Number of tokens per data format, kept in sync with the format index
+    pub format_counts: Map<'a, &'a str, u64>,
+    /// Number of tokens held by each owner, owners without tokens are removed
+    pub balances: Map<'a, &'a Addr, u64>,
+    /// Tokens keyed by their numeric id, see `token_key`
+    pub tokens: IndexedMap<'a, U64Key, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Tokens keyed by their id string, as stored before the numeric keys. Only read by the migration
+    pub legacy_tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
 
     pub(crate) _custom_response: PhantomData<C>,
 }
@@ -44,62 +87,154 @@ where
     T: Serialize + DeserializeOwned + Clone,
 {
     fn default() -> Self {
-        Self::new(
-            "nft_info",
-            "admin",
-            "token_uri",
-            "merkle_root",
-            "max_issuance",
-            "num_tokens",
-            "denom",
-            "price",
-            "open_mint_time",
-            "whitelist_mint_time",
-            "claimed",
-            "operators",
-            "tokens",
-            "tokens__owner",
-        )
+        Self::new(STORAGE_KEYS)
     }
 }
 
+/// Storage namespace of every Item and Map of the contract, see STORAGE.md for the raw layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StorageKeys<'a> {
+    pub contract_info: &'a str,
+    pub admin: &'a str,
+    pub token_uri: &'a str,
+    pub max_issuance: &'a str,
+    pub token_count: &'a str,
+    pub denom: &'a str,
+    pub price: &'a str,
+    pub merkle_root: &'a str,
+    pub whitelist_signer: &'a str,
+    pub whitelist_mint_time: &'a str,
+    pub open_mint_time: &'a str,
+    pub random_mint: &'a str,
+    pub website_url: &'a str,
+    pub edit_count: &'a str,
+    pub storage_layout: &'a str,
+    pub hooks: &'a str,
+    pub sale_phases: &'a str,
+    pub id_pool: &'a str,
+    pub operators: &'a str,
+    pub operator_grants: &'a str,
+    pub spender_approvals: &'a str,
+    pub claimed: &'a str,
+    pub phase_minted: &'a str,
+    pub phase_claims: &'a str,
+    pub profiles: &'a str,
+    pub recent_edits: &'a str,
+    pub format_counts: &'a str,
+    pub balances: &'a str,
+    pub tokens: &'a str,
+    pub tokens_owner: &'a str,
+    pub tokens_format: &'a str,
+    pub legacy_tokens: &'a str,
+    pub legacy_tokens_owner: &'a str,
+    pub legacy_tokens_format: &'a str,
+}
+
+pub const STORAGE_KEYS: StorageKeys<'static> = StorageKeys {
+    contract_info: "nft_info",
+    admin: "admin",
+    token_uri: "token_uri",
+    max_issuance: "max_issuance",
+    token_count: "num_tokens",
+    denom: "denom",
+    price: "price",
+    merkle_root: "merkle_root",
+    whitelist_signer: "whitelist_signer",
+    whitelist_mint_time: "whitelist_mint_time",
+    open_mint_time: "open_mint_time",
+    random_mint: "random_mint",
+    website_url: "website_url",
+    edit_count: "edit_count",
+    storage_layout: "storage_layout",
+    hooks: "hooks",
+    sale_phases: "sale_phases",
+    id_pool: "id_pool",
+    operators: "operators",
+    operator_grants: "operator_grants",
+    spender_approvals: "spender_approvals",
+    claimed: "claimed",
+    phase_minted: "phase_minted",
+    phase_claims: "phase_claims",
+    profiles: "profiles",
+    recent_edits: "recent_edits",
+    format_counts: "format_counts",
+    balances: "balances",
+    tokens: "token_ids",
+    tokens_owner: "token_ids__owner",
+    tokens_format: "token_ids__format",
+    // Where the string keyed tokens ended up before they moved to numeric keys
+    legacy_tokens: "denom",
+    legacy_tokens_owner: "price",
+    legacy_tokens_format: "tokens__format",
+};
+
+/// Namespaces used before storage layout 1, when `new` took its keys as
+/// positional arguments and `default` passed some of them in the wrong order
+pub const LEGACY_STORAGE_KEYS: StorageKeys<'static> = StorageKeys {
+    max_issuance: "merkle_root",
+    token_count: "max_issuance",
+    denom: "open_mint_time",
+    price: "whitelist_mint_time",
+    merkle_root: "claimed",
+    whitelist_mint_time: "tokens",
+    open_mint_time: "operators",
+    operators: "num_tokens",
+    claimed: "tokens__owner",
+    ..STORAGE_KEYS
+};
+
+/// Version of the storage layout written by this code, see `migrate_storage_layout`
+pub const STORAGE_LAYOUT: u64 = 2;
+
 impl<'a, T, C> Cw721Contract<'a, T, C>
 where
     T: Serialize + DeserializeOwned + Clone,
 {
-    fn new(
-        contract_key: &'a str,
-        admin_key: &'a str,
-        token_uri_key: &'a str,
-        max_issuance: &'a str,
-        token_count_key: &'a str,
-        operator_key: &'a str,
-        tokens_key: &'a str,
-        tokens_owner_key: &'a str,
-        denom_key: &'a str,
-        price_key: &'a str,
-        merkle_root_key: &'a str,
-        open_mint_time_key: &'a str,
-        whitelist_mint_time_key: &'a str,
-        claimed_key: &'a str,
-    ) -> Self {
+    pub fn new(keys: StorageKeys<'a>) -> Self {
         let indexes = TokenIndexes {
-            owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
+            owner: MultiIndex::new(token_owner_idx, keys.tokens, keys.tokens_owner),
+            format: MultiIndex::new(token_format_idx, keys.tokens, keys.tokens_format),
+        };
+        let legacy_indexes = TokenIndexes {
+            owner: MultiIndex::new(token_owner_idx, keys.legacy_tokens, keys.legacy_tokens_owner),
+            format: MultiIndex::new(
+                token_format_idx,
+                keys.legacy_tokens,
+                keys.legacy_tokens_format,
+            ),
         };
         Self {
-            contract_info: Item::new(contract_key),
-            admin: Item::new(admin_key),
-            token_uri: Item::new(token_uri_key),
-            max_issuance: Item::new(max_issuance),
-            token_count: Item::new(token_count_key),
-            denom: Item::new(denom_key),
-            price: Item::new(price_key),
-            merkle_root: Item::new(merkle_root_key),
-            open_mint_time: Item::new(open_mint_time_key),
-            whitelist_mint_time: Item::new(whitelist_mint_time_key),
-            claimed: Map::new(claimed_key),
-            operators: Map::new(operator_key),
-            tokens: IndexedMap::new(tokens_key, indexes),
+            contract_info: Item::new(keys.contract_info),
+            admin: Item::new(keys.admin),
+            token_uri: Item::new(keys.token_uri),
+            max_issuance: Item::new(keys.max_issuance),
+            token_count: Item::new(keys.token_count),
+            denom: Item::new(keys.denom),
+            price: Item::new(keys.price),
+            merkle_root: Item::new(keys.merkle_root),
+            whitelist_signer: Item::new(keys.whitelist_signer),
+            open_mint_time: Item::new(keys.open_mint_time),
+            whitelist_mint_time: Item::new(keys.whitelist_mint_time),
+            random_mint: Item::new(keys.random_mint),
+            website_url: Item::new(keys.website_url),
+            edit_count: Item::new(keys.edit_count),
+            storage_layout: Item::new(keys.storage_layout),
+            hooks: Item::new(keys.hooks),
+            sale_phases: Item::new(keys.sale_phases),
+            recent_edits: Map::new(keys.recent_edits),
+            format_counts: Map::new(keys.format_counts),
+            balances: Map::new(keys.balances),
+            id_pool: Map::new(keys.id_pool),
+            claimed: Map::new(keys.claimed),
+            legacy_claimed: Map::new(keys.claimed),
+            phase_minted: Map::new(keys.phase_minted),
+            phase_claims: Map::new(keys.phase_claims),
+            profiles: Map::new(keys.profiles),
+            operators: Map::new(keys.operators),
+            operator_grants: Map::new(keys.operator_grants),
+            spender_approvals: Map::new(keys.spender_approvals),
+            tokens: IndexedMap::new(keys.tokens, indexes),
+            legacy_tokens: IndexedMap::new(keys.legacy_tokens, legacy_indexes),
             _custom_response: PhantomData,
         }
     }
@@ -113,6 +248,274 @@ where
         self.token_count.save(storage, &val)?;
         Ok(val)
     }
+
+    pub fn format_count(&self, storage: &dyn Storage, format: DataFormat) -> StdResult<u64> {
+        Ok(self
+            .format_counts
+            .may_load(storage, format.as_str())?
+            .unwrap_or_default())
+    }
+
+    /// Moves a token between the format counters, `from` is None for newly minted tokens
+    pub fn update_format_count(
+        &self,
+        storage: &mut dyn Storage,
+        from: Option<DataFormat>,
+        to: DataFormat,
+    ) -> StdResult<()> {
+        if from == Some(to) {
+            return Ok(());
+        }
+        if let Some(from) = from {
+            let count = self.format_count(storage, from)?;
+            self.format_counts
+                .save(storage, from.as_str(), &count.saturating_sub(1))?;
+        }
+        let count = self.format_count(storage, to)?;
+        self.format_counts.save(storage, to.as_str(), &(count + 1))
+    }
+
+    pub fn balance(&self, storage: &dyn Storage, owner: &Addr) -> StdResult<u64> {
+        Ok(self.balances.may_load(storage, owner)?.unwrap_or_default())
+    }
+
+    /// Moves one token from the balance of `from` to the balance of `to`,
+    /// `from` is None for newly minted tokens
+    pub fn move_balance(
+        &self,
+        storage: &mut dyn Storage,
+        from: Option<&Addr>,
+        to: &Addr,
+    ) -> StdResult<()> {
+        if from == Some(to) {
+            return Ok(());
+        }
+        if let Some(from) = from {
+            match self.balance(storage, from)? {
+                0 | 1 => self.balances.remove(storage, from),
+                count => self.balances.save(storage, from, &(count - 1))?,
+            }
+        }
+        let count = self.balance(storage, to)?;
+        self.balances.save(storage, to, &(count + 1))
+    }
+
+    /// Grants `operator` control over the tokens of `granter` in both directions
+    pub fn save_operator(
+        &self,
+        storage: &mut dyn Storage,
+        granter: &Addr,
+        operator: &Addr,
+        expires: &Expiration,
+    ) -> StdResult<()> {
+        self.operators.save(storage, (granter, operator), expires)?;
+        self.operator_grants.save(storage, (operator, granter), expires)
+    }
+
+    pub fn remove_operator(&self, storage: &mut dyn Storage, granter: &Addr, operator: &Addr) {
+        self.operators.remove(storage, (granter, operator));
+        self.operator_grants.remove(storage, (operator, granter));
+    }
+
+    /// Points the spender index at the new approvals of a token, `old` being the approvals
+    /// stored before
+    pub fn update_spender_approvals(
+        &self,
+        storage: &mut dyn Storage,
+        key: &U64Key,
+        old: &[Approval],
+        new: &[Approval],
+    ) -> StdResult<()> {
+        for approval in old {
+            self.spender_approvals
+                .remove(storage, (&approval.spender, key.clone()));
+        }
+        for approval in new {
+            self.spender_approvals
+                .save(storage, (&approval.spender, key.clone()), &approval.expires)?;
+        }
+        Ok(())
+    }
+
+    /// Fills the operator reverse index from the grants stored before it existed, the spender
+    /// index is filled as `migrate_tokens` moves the tokens
+    pub fn rebuild_operator_grants(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        let operators: Vec<(Vec<u8>, Expiration)> = self
+            .operators
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        for (key, expires) in operators {
+            let (granter, operator) = split_pair_key(&key)?;
+            self.operator_grants
+                .save(storage, (&operator, &granter), &expires)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the next `limit` tokens stored under their id string after `start_after` to their
+    /// numeric key, counting them by format and owner and indexing their spenders. Returns the
+    /// ids of the tokens moved
+    pub fn migrate_tokens(
+        &self,
+        storage: &mut dyn Storage,
+        start_after: Option<String>,
+        limit: usize,
+    ) -> StdResult<Vec<String>> {
+        let start = start_after.map(|id| Bound::exclusive(id.as_bytes()));
+        let legacy_keys: Vec<Vec<u8>> = self
+            .legacy_tokens
+            .prefix(())
+            .keys(storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect();
+        let mut moved = Vec::with_capacity(legacy_keys.len());
+        for key in legacy_keys {
+            let token_id = String::from_utf8(key).map_err(StdError::invalid_utf8)?;
+            let token = self.legacy_tokens.load(storage, &token_id)?;
+            self.legacy_tokens.remove(storage, &token_id)?;
+            let key = token_key(&token_id)?;
+            self.tokens.save(storage, key.clone(), &token)?;
+            self.index_token(storage, &key, &token)?;
+            moved.push(token_id);
+        }
+        Ok(moved)
+    }
+
+    /// Adds a token to the counters and the spender index `migrate_tokens` rebuilds
+    fn index_token(
+        &self,
+        storage: &mut dyn Storage,
+        key: &U64Key,
+        token: &TokenInfo<T>,
+    ) -> StdResult<()> {
+        self.update_format_count(storage, None, DataFormat::of(&token.data))?;
+        self.move_balance(storage, None, &token.owner)?;
+        self.update_spender_approvals(storage, key, &[], &token.approvals)
+    }
+
+    /// Brings the storage up to `STORAGE_LAYOUT`: layout 1 moves the state written under
+    /// `LEGACY_STORAGE_KEYS` to its own namespace, layout 2 turns the claimed flags into
+    /// counts. Does nothing once the storage layout is current
+    pub fn migrate_storage_layout(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        let layout = self.storage_layout.may_load(storage)?.unwrap_or_default();
+        if layout >= STORAGE_LAYOUT {
+            return Ok(());
+        }
+        if layout < 1 {
+            self.move_legacy_namespaces(storage)?;
+        }
+
+        let claimed: Vec<(Vec<u8>, bool)> = self
+            .legacy_claimed
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        for (key, claimed) in claimed {
+            let address = Addr::unchecked(String::from_utf8(key).map_err(StdError::invalid_utf8)?);
+            if claimed {
+                self.claimed.save(storage, &address, &1)?;
+            } else {
+                self.claimed.remove(storage, &address);
+            }
+        }
+
+        self.storage_layout.save(storage, &STORAGE_LAYOUT)
+    }
+
+    fn move_legacy_namespaces(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        let legacy = Cw721Contract::<T, C>::new(LEGACY_STORAGE_KEYS);
+
+        // The old and new keys of the items overlap, so everything is read before writing
+        let max_issuance = legacy.max_issuance.may_load(storage)?;
+        let token_count = legacy.token_count.may_load(storage)?;
+        let denom = legacy.denom.may_load(storage)?;
+        let price = legacy.price.may_load(storage)?;
+        let merkle_root = legacy.merkle_root.may_load(storage)?;
+        let whitelist_mint_time = legacy.whitelist_mint_time.may_load(storage)?;
+        let open_mint_time = legacy.open_mint_time.may_load(storage)?;
+        let operators: Vec<(Vec<u8>, Expiration)> = legacy
+            .operators
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        let claimed: Vec<(Vec<u8>, bool)> = legacy
+            .legacy_claimed
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+
+        legacy.max_issuance.remove(storage);
+        legacy.token_count.remove(storage);
+        legacy.denom.remove(storage);
+        legacy.price.remove(storage);
+        legacy.merkle_root.remove(storage);
+        legacy.whitelist_mint_time.remove(storage);
+        legacy.open_mint_time.remove(storage);
+
+        move_item(storage, &self.max_issuance, max_issuance)?;
+        move_item(storage, &self.token_count, token_count)?;
+        move_item(storage, &self.denom, denom)?;
+        move_item(storage, &self.price, price)?;
+        move_item(storage, &self.merkle_root, merkle_root)?;
+        move_item(storage, &self.whitelist_mint_time, whitelist_mint_time)?;
+        move_item(storage, &self.open_mint_time, open_mint_time)?;
+        for (key, expires) in operators {
+            let (granter, operator) = split_pair_key(&key)?;
+            legacy.operators.remove(storage, (&granter, &operator));
+            self.operators.save(storage, (&granter, &operator), &expires)?;
+        }
+        for (key, claimed) in claimed {
+            let address = Addr::unchecked(String::from_utf8(key).map_err(StdError::invalid_utf8)?);
+            legacy.legacy_claimed.remove(storage, &address);
+            self.legacy_claimed.save(storage, &address, &claimed)?;
+        }
+        Ok(())
+    }
+
+    /// Appends an edit to the recent edits feed, dropping the oldest entry once the feed is full
+    pub fn record_edit(
+        &self,
+        storage: &mut dyn Storage,
+        token_id: &str,
+        editor: &Addr,
+        time: Timestamp,
+    ) -> StdResult<u64> {
+        let edit_id = self.edit_count.may_load(storage)?.unwrap_or_default() + 1;
+        self.edit_count.save(storage, &edit_id)?;
+        let edit = EditInfo {
+            token_id: token_id.to_string(),
+            editor: editor.clone(),
+            time,
+        };
+        self.recent_edits.save(storage, U64Key::new(edit_id), &edit)?;
+        if edit_id > MAX_RECENT_EDITS {
+            self.recent_edits
+                .remove(storage, U64Key::new(edit_id - MAX_RECENT_EDITS));
+        }
+        Ok(edit_id)
+    }
+
+    /// Draws one id out of the `remaining` unminted ids with a sparse Fisher-Yates shuffle.
+    /// Slot `i` holds id `i + 1` until it is swapped, so every id is returned exactly once.
+    pub fn draw_token_id(
+        &self,
+        storage: &mut dyn Storage,
+        remaining: u64,
+        seed: [u8; 32],
+    ) -> StdResult<u64> {
+        let last = remaining - 1;
+        let index = u64::from_be_bytes(seed[..8].try_into().expect("Wrong length")) % remaining;
+        let id = self
+            .id_pool
+            .may_load(storage, U64Key::new(index))?
+            .unwrap_or(index + 1);
+        let last_id = self
+            .id_pool
+            .may_load(storage, U64Key::new(last))?
+            .unwrap_or(last + 1);
+        if index != last {
+            self.id_pool.save(storage, U64Key::new(index), &last_id)?;
+        }
+        self.id_pool.remove(storage, U64Key::new(last));
+        Ok(id)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -134,6 +537,61 @@ pub struct TokenInfo<T> {
     pub extension: T,
 }
 
+/// Planet data format, read from the prefix of the data string
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DataFormat {
+    /// The planet was never edited
+    None,
+    /// Geodesic planet, data starts with "GEO1="
+    Geo1,
+    /// Voxel planet, data starts with "VOX1="
+    Vox1,
+    /// Any other data
+    Other,
+}
+
+impl DataFormat {
+    /// Only looks at the format tag, the contract stores the data without decoding it. The tags
+    /// match `GEO_FORMAT` and `VOX_FORMAT` of the planet-data package, which is not a dependency
+    /// because its renderer and mesh code use floats, refused by the chain
+    pub fn of(data: &Option<String>) -> Self {
+        match data.as_deref() {
+            None => DataFormat::None,
+            Some(data) if data.starts_with("GEO1=") => DataFormat::Geo1,
+            Some(data) if data.starts_with("VOX1=") => DataFormat::Vox1,
+            Some(_) => DataFormat::Other,
+        }
+    }
+
+    /// Key of the format in the format index and counters
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataFormat::None => "none",
+            DataFormat::Geo1 => "geo1",
+            DataFormat::Vox1 => "vox1",
+            DataFormat::Other => "other",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EditInfo {
+    pub token_id: String,
+    /// Account that sent the update
+    pub editor: Addr,
+    /// Block time of the update
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlanetProfile {
+    /// Custom planet name, replaces the default "Planet #id"
+    pub name: Option<String>,
+    /// Free text description of the planet
+    pub description: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Approval {
     /// Account that can transfer/send the token
@@ -155,6 +613,7 @@ where
 {
     // pk goes to second tuple element
     pub owner: MultiIndex<'a, (Addr, Vec<u8>), TokenInfo<T>>,
+    pub format: MultiIndex<'a, (Vec<u8>, Vec<u8>), TokenInfo<T>>,
 }
 
 impl<'a, T> IndexList<TokenInfo<T>> for TokenIndexes<'a, T>
@@ -162,11 +621,64 @@ where
     T: Serialize + DeserializeOwned + Clone,
 {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<TokenInfo<T>>> + '_> {
-        let v: Vec<&dyn Index<TokenInfo<T>>> = vec![&self.owner];
+        let v: Vec<&dyn Index<TokenInfo<T>>> = vec![&self.owner, &self.format];
         Box::new(v.into_iter())
     }
 }
 
+/// Storage key of a token id. Ids are stored as big endian numbers so that tokens enumerate
+/// in numeric order, only the plain decimal form of an id is accepted
+pub fn token_key(token_id: &str) -> StdResult<U64Key> {
+    match token_id.parse::<u64>() {
+        Ok(id) if id.to_string() == token_id => Ok(U64Key::new(id)),
+        _ => Err(StdError::generic_err(format!("Invalid token id: {}", token_id))),
+    }
+}
+
+/// Start bound of a token enumeration, tokens are ordered by their numeric id
+pub fn token_bound(start_after: Option<String>) -> StdResult<Option<Bound>> {
+    start_after
+        .map(|token_id| token_key(&token_id).map(Bound::exclusive))
+        .transpose()
+}
+
+/// Token id of a storage key written by `token_key`
+pub fn token_id_from_key(key: &[u8]) -> StdResult<String> {
+    let id: [u8; 8] = key
+        .try_into()
+        .map_err(|_| StdError::generic_err("Invalid token key"))?;
+    Ok(u64::from_be_bytes(id).to_string())
+}
+
+fn move_item<T>(storage: &mut dyn Storage, item: &Item<T>, value: Option<T>) -> StdResult<()>
+where
+    T: Serialize + DeserializeOwned,
+{
+    match value {
+        Some(value) => item.save(storage, &value),
+        None => Ok(()),
+    }
+}
+
+/// Addresses of a `(&Addr, &Addr)` map key, the first one being length prefixed
+fn split_pair_key(key: &[u8]) -> StdResult<(Addr, Addr)> {
+    let invalid = || StdError::generic_err("Invalid address pair key");
+    let length = key.get(..2).ok_or_else(invalid)?;
+    let length = u16::from_be_bytes([length[0], length[1]]) as usize;
+    let first = key.get(2..2 + length).ok_or_else(invalid)?;
+    let second = &key[2 + length..];
+    let address = |bytes: &[u8]| {
+        String::from_utf8(bytes.to_vec())
+            .map(Addr::unchecked)
+            .map_err(StdError::invalid_utf8)
+    };
+    Ok((address(first)?, address(second)?))
+}
+
 pub fn token_owner_idx<T>(d: &TokenInfo<T>, k: Vec<u8>) -> (Addr, Vec<u8>) {
     (d.owner.clone(), k)
 }
+
+pub fn token_format_idx<T>(d: &TokenInfo<T>, k: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
+    (DataFormat::of(&d.data).as_str().as_bytes().to_vec(), k)
+}
@@ -0,0 +1,68 @@
+use cosmwasm_std::{Addr, Api, Binary, Env, Timestamp};
+use sha2::Digest;
+
+use crate::error::ContractError;
+use crate::msg::WhitelistVoucher;
+
+/// Bytes the whitelist signer signs for a voucher: the contract address, the minter address
+/// and the sale phase name (empty without sale phases), each prefixed by its length as a
+/// 2 byte big endian number, then the allocation and the expiry as 8 byte big endian numbers
+pub fn voucher_message(
+    contract: &str,
+    minter: &str,
+    phase: &str,
+    allocation: u64,
+    expires: u64,
+) -> Vec<u8> {
+    let mut message = vec![];
+    for field in [contract, minter, phase] {
+        message.extend_from_slice(&(field.len() as u16).to_be_bytes());
+        message.extend_from_slice(field.as_bytes());
+    }
+    message.extend_from_slice(&allocation.to_be_bytes());
+    message.extend_from_slice(&expires.to_be_bytes());
+    message
+}
+
+/// Checks that the voucher of `minter` for `phase` is unexpired and signed by the signer
+pub fn check_voucher(
+    api: &dyn Api,
+    env: &Env,
+    signer: &Binary,
+    minter: &Addr,
+    phase: &str,
+    voucher: &WhitelistVoucher,
+) -> Result<(), ContractError> {
+    if env.block.time >= Timestamp::from_seconds(voucher.expires) {
+        return Err(ContractError::VoucherExpired {});
+    }
+    verify_voucher(api, signer, &env.contract.address, minter, phase, voucher)
+}
+
+/// Checks the voucher signature of `minter` against the signer public key.
+/// The signature is the 64 byte compact secp256k1 signature of the sha256 of `voucher_message`
+pub fn verify_voucher(
+    api: &dyn Api,
+    signer: &Binary,
+    contract: &Addr,
+    minter: &Addr,
+    phase: &str,
+    voucher: &WhitelistVoucher,
+) -> Result<(), ContractError> {
+    let message = voucher_message(
+        contract.as_str(),
+        minter.as_str(),
+        phase,
+        voucher.allocation,
+        voucher.expires,
+    );
+    let hash = sha2::Sha256::digest(&message);
+    let valid = api
+        .secp256k1_verify(&hash, &voucher.signature, signer)
+        .map_err(|_| ContractError::VerificationFailed {})?;
+    if valid {
+        Ok(())
+    } else {
+        Err(ContractError::VerificationFailed {})
+    }
+}
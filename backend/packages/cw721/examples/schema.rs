@@ -22,7 +22,7 @@ fn main() {
     export_schema(&schema_for!(Cw721QueryMsg), &out_dir);
     export_schema(&schema_for!(Cw721ReceiveMsg), &out_dir);
     export_schema_with_title(
-        &schema_for!(AllNftInfoResponse),
+        &schema_for!(AllNftInfoResponse<Extension>),
         &out_dir,
         "AllNftInfoResponse",
     );
@@ -30,7 +30,7 @@ fn main() {
     export_schema(&schema_for!(ContractInfoResponse), &out_dir);
     export_schema(&schema_for!(OwnerOfResponse), &out_dir);
     export_schema_with_title(
-        &schema_for!(NftInfoResponse),
+        &schema_for!(NftInfoResponse<Extension>),
         &out_dir,
         "NftInfoResponse",
     );
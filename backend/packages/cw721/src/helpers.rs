@@ -95,7 +95,7 @@ impl Cw721Contract {
         &self,
         querier: &Q,
         token_id: T,
-    ) -> StdResult<NftInfoResponse> {
+    ) -> StdResult<NftInfoResponse<U>> {
         let req = Cw721QueryMsg::NftInfo {
             token_id: token_id.into(),
         };
@@ -108,7 +108,7 @@ impl Cw721Contract {
         querier: &Q,
         token_id: T,
         include_expired: bool,
-    ) -> StdResult<AllNftInfoResponse> {
+    ) -> StdResult<AllNftInfoResponse<U>> {
         let req = Cw721QueryMsg::AllNftInfo {
             token_id: token_id.into(),
             include_expired: Some(include_expired),
@@ -0,0 +1,59 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, Binary, CosmosMsg, StdResult, WasmMsg};
+
+/// What happened to the planet
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanetEvent {
+    Mint,
+    Transfer,
+    Update,
+}
+
+/// PlanetHookMsg is sent by cw721-data to every registered hook contract when a
+/// planet is minted, transferred or edited. It should be de/serialized under
+/// `PlanetHook()` variant in a ExecuteMsg
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct PlanetHookMsg {
+    pub token_id: String,
+    pub event: PlanetEvent,
+    /// Owner before the event, unset on mint
+    pub old_owner: Option<String>,
+    /// Owner after the event
+    pub new_owner: String,
+    /// Hex encoded sha256 of the planet data after the event, unset when there is no data
+    pub data_hash: Option<String>,
+}
+
+impl PlanetHookMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = HookExecuteMsg::PlanetHook(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>, C>(self, contract_addr: T) -> StdResult<CosmosMsg<C>>
+    where
+        C: Clone + std::fmt::Debug + PartialEq + JsonSchema,
+    {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+/// This is just a helper to properly serialize the above message.
+/// The hook contract should include this variant in the larger ExecuteMsg enum
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+enum HookExecuteMsg {
+    PlanetHook(PlanetHookMsg),
+}
@@ -1,4 +1,5 @@
 mod helpers;
+mod hook;
 mod msg;
 mod query;
 mod receiver;
@@ -7,10 +8,11 @@ mod traits;
 pub use cw0::Expiration;
 
 pub use crate::helpers::Cw721Contract;
+pub use crate::hook::{PlanetEvent, PlanetHookMsg};
 pub use crate::msg::Cw721ExecuteMsg;
 pub use crate::query::{
     AllNftInfoResponse, Approval, ApprovedForAllResponse, ContractInfoResponse, Cw721QueryMsg,
-    NftInfoResponse, NumTokensResponse, OwnerOfResponse, TokensResponse, NftDataExtension,
+    NftInfoResponse, NumTokensResponse, OwnerOfResponse, TokensResponse, NftDataExtension, Trait,
 };
 pub use crate::receiver::Cw721ReceiveMsg;
 pub use crate::traits::{CustomMsg, Cw721, Cw721Execute, Cw721Query};
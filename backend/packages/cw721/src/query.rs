@@ -4,11 +4,29 @@ use serde::{Deserialize, Serialize};
 use cw0::Expiration;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct NftDataExtension {
+pub struct NftDataExtension<T> {
     /// Token Image
     pub image: Option<String>,
     /// Token Name
     pub name: String,
+    /// Token Description
+    pub description: Option<String>,
+    /// Page of the token on the project website
+    pub external_url: Option<String>,
+    /// Interactive view of the token, shown by marketplaces that support it
+    pub animation_url: Option<String>,
+    /// Traits of the token in the ERC721 metadata format
+    pub attributes: Vec<Trait>,
+    /// Custom metadata stored on the token by contracts that extend cw721-data
+    pub custom: T,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Trait {
+    /// How marketplaces should display the value, ie "number"
+    pub display_type: Option<String>,
+    pub trait_type: String,
+    pub value: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -99,7 +117,7 @@ pub struct ContractInfoResponse {
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct NftInfoResponse {
+pub struct NftInfoResponse<T> {
     /// "A URI pointing to a resource with mime type image/* representing the asset to which this
     /// NFT represents. Consider making any images at a width between 320 and 1080 pixels and aspect
     /// ratio between 1.91:1 and 4:5 inclusive.
@@ -107,15 +125,15 @@ pub struct NftInfoResponse {
     pub image: Option<String>,
 
     /// You can add any custom metadata here when you extend cw721-base
-    pub extension: NftDataExtension,
+    pub extension: NftDataExtension<T>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct AllNftInfoResponse {
+pub struct AllNftInfoResponse<T> {
     /// Who can transfer the token
     pub access: OwnerOfResponse,
     /// Data on the token itself,
-    pub info: NftInfoResponse,
+    pub info: NftInfoResponse<T>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -0,0 +1,250 @@
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::exit;
+
+use planet_data::export::{to_gltf, to_magica_voxel, to_obj};
+use planet_data::import::{from_magica_voxel, Fit};
+use planet_data::render::DEFAULT_THUMBNAIL_SIZE;
+use planet_data::{diff, thumbnail, PlanetData};
+
+const USAGE: &str = "Usage: planet <command> [arguments]
+
+Inputs are a data string, a file holding one, or - for stdin.
+
+Commands:
+  decode <data>          print the planet as JSON
+  encode <json>          print the data string of a JSON planet
+  validate <data>...     check data strings against the format rules
+  diff <old> <new>       list the changes between two revisions of a planet
+  render <data> <png>    draw a thumbnail of the planet
+  export <data> <file>   write the terrain as a MagicaVoxel .vox (VOX1 only), a
+                         Wavefront .obj or a binary glTF .glb, after the extension
+  thumbnails <tokens> <dir>
+                         draw a thumbnail named <token id>.png for every token of a
+                         NftsData, AllNftsData or OwnerNftsData query response, or of
+                         a file with a \"<token id> <data>\" pair per line
+  import <model.vox>     print the VOX1 data string of a MagicaVoxel model
+
+Options:
+  --size <pixels>        thumbnail width and height, 256 by default
+  --fit <reject|crop|scale>
+                         how import handles models larger than 16 blocks a side
+                         once trimmed, reject by default
+  --token <id>           make import print an update message for the token";
+
+/// Options that may appear anywhere among the arguments
+struct Options {
+    size: u32,
+    fit: Fit,
+    token: Option<String>,
+}
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match take_options(&mut args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
+    };
+    let result = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["decode", input] => decode(input),
+        ["encode", input] => encode(input),
+        ["validate", ref inputs @ ..] if !inputs.is_empty() => validate(inputs),
+        ["diff", old, new] => show_diff(old, new),
+        ["render", input, output] => render(input, output, options.size),
+        ["export", input, output] => export(input, output),
+        ["import", model] => import(model, &options),
+        ["thumbnails", tokens, directory] => thumbnails(tokens, directory, options.size),
+        _ => Err(USAGE.to_string()),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        exit(1);
+    }
+}
+
+fn decode(input: &str) -> Result<(), String> {
+    let planet = read_planet(input)?;
+    let json = serde_json::to_string_pretty(&planet).map_err(|err| err.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn encode(input: &str) -> Result<(), String> {
+    let json = read_input(input)?;
+    let planet: PlanetData = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+    println!("{}", planet.encode().map_err(|err| err.to_string())?);
+    Ok(())
+}
+
+fn validate(inputs: &[&str]) -> Result<(), String> {
+    let mut invalid = 0;
+    for input in inputs {
+        match read_planet(input) {
+            Ok(_) => println!("{}: ok", input),
+            Err(err) => {
+                println!("{}: {}", input, err);
+                invalid += 1;
+            }
+        }
+    }
+    if invalid > 0 {
+        return Err(format!("{} of {} planets are invalid", invalid, inputs.len()));
+    }
+    Ok(())
+}
+
+fn show_diff(old: &str, new: &str) -> Result<(), String> {
+    let changes = diff(&read_planet(old)?, &read_planet(new)?);
+    if changes.is_empty() {
+        println!("no changes");
+    }
+    for change in changes {
+        println!("{}", change);
+    }
+    Ok(())
+}
+
+fn render(input: &str, output: &str, size: u32) -> Result<(), String> {
+    let png = thumbnail(read_input(input)?.trim(), size).map_err(|err| err.to_string())?;
+    fs::write(output, png).map_err(|err| format!("Cannot write {}: {}", output, err))
+}
+
+fn export(input: &str, output: &str) -> Result<(), String> {
+    let planet = read_planet(input)?;
+    let mesh = match &planet {
+        PlanetData::Geo(planet) => planet.terrain_mesh(),
+        PlanetData::Vox(planet) => planet.terrain_mesh(),
+    };
+    let extension = Path::new(output).extension().and_then(|extension| extension.to_str());
+    let file = match (extension, &planet) {
+        (Some("vox"), PlanetData::Vox(planet)) => to_magica_voxel(planet),
+        (Some("vox"), PlanetData::Geo(_)) => {
+            return Err("Only VOX1 planets can be exported to .vox".to_string())
+        }
+        (Some("obj"), _) => to_obj(&mesh).into_bytes(),
+        (Some("glb"), _) => to_gltf(&mesh),
+        _ => return Err(format!("Cannot export to {}, use .vox, .obj or .glb", output)),
+    };
+    fs::write(output, file).map_err(|err| format!("Cannot write {}: {}", output, err))
+}
+
+fn import(model: &str, options: &Options) -> Result<(), String> {
+    let file = fs::read(model).map_err(|err| format!("Cannot read {}: {}", model, err))?;
+    let planet = from_magica_voxel(&file, options.fit).map_err(|err| err.to_string())?;
+    let data = planet.encode().map_err(|err| err.to_string())?;
+    match &options.token {
+        Some(token_id) => {
+            let msg = serde_json::json!({ "update": { "token_id": token_id, "data": data } });
+            println!("{}", msg);
+        }
+        None => println!("{}", data),
+    }
+    Ok(())
+}
+
+fn thumbnails(tokens: &str, directory: &str, size: u32) -> Result<(), String> {
+    let tokens = read_tokens(&read_input(tokens)?)?;
+    fs::create_dir_all(directory).map_err(|err| format!("Cannot create {}: {}", directory, err))?;
+    let mut failed = 0;
+    for (token_id, data) in &tokens {
+        let output = Path::new(directory).join(format!("{}.png", token_id));
+        let result = match data {
+            Some(data) => thumbnail(data, size).map_err(|err| err.to_string()),
+            None => Err("no data".to_string()),
+        };
+        match result.and_then(|png| fs::write(&output, png).map_err(|err| err.to_string())) {
+            Ok(()) => println!("{}: {}", token_id, output.display()),
+            Err(err) => {
+                println!("{}: {}", token_id, err);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} thumbnails failed", failed, tokens.len()));
+    }
+    Ok(())
+}
+
+/// Token ids and data of a query response listing `nfts`, or of "<token id> <data>" lines
+fn read_tokens(input: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    if !input.trim_start().starts_with('{') {
+        return input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match line.split_once(char::is_whitespace) {
+                Some((token_id, data)) => Ok((token_id.to_string(), Some(data.trim().to_string()))),
+                None => Err(format!("Expected a token id and data, found \"{}\"", line)),
+            })
+            .collect();
+    }
+    let response: serde_json::Value = serde_json::from_str(input).map_err(|err| err.to_string())?;
+    let nfts = response["nfts"]
+        .as_array()
+        .ok_or_else(|| "Expected a query response with a list of nfts".to_string())?;
+    nfts.iter()
+        .map(|nft| {
+            let token_id = nft["token_id"]
+                .as_str()
+                .ok_or_else(|| "Missing token_id in the query response".to_string())?;
+            Ok((token_id.to_string(), nft["data"].as_str().map(str::to_string)))
+        })
+        .collect()
+}
+
+fn take_options(args: &mut Vec<String>) -> Result<Options, String> {
+    let size = match take_option(args, "--size")? {
+        Some(size) => size
+            .parse()
+            .ok()
+            .filter(|size| *size > 0)
+            .ok_or_else(|| "--size expects a number of pixels".to_string())?,
+        None => DEFAULT_THUMBNAIL_SIZE,
+    };
+    let fit = match take_option(args, "--fit")?.as_deref() {
+        None | Some("reject") => Fit::Reject,
+        Some("crop") => Fit::Crop,
+        Some("scale") => Fit::Scale,
+        Some(fit) => return Err(format!("Unknown fit {}, use reject, crop or scale", fit)),
+    };
+    let token = take_option(args, "--token")?;
+    Ok(Options { size, fit, token })
+}
+
+/// Removes `name <value>` from the arguments
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let position = match args.iter().position(|arg| arg == name) {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+    if position + 1 >= args.len() {
+        return Err(format!("{} expects a value", name));
+    }
+    let value = args.remove(position + 1);
+    args.remove(position);
+    Ok(Some(value))
+}
+
+fn read_planet(input: &str) -> Result<PlanetData, String> {
+    PlanetData::decode(read_input(input)?.trim()).map_err(|err| err.to_string())
+}
+
+/// Literal data strings and JSON are used as is, anything else is read as a file
+fn read_input(input: &str) -> Result<String, String> {
+    if input == "-" {
+        let mut buffer = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        Ok(buffer)
+    } else if input.starts_with("GEO1=") || input.starts_with("VOX1=") || input.starts_with('{') {
+        Ok(input.to_string())
+    } else {
+        fs::read_to_string(input).map_err(|err| format!("Cannot read {}: {}", input, err))
+    }
+}
@@ -0,0 +1,82 @@
+use crate::error::PlanetDataError;
+
+const CHARACTER_TABLE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Packs values of any bit width into base64 characters, most significant bit first.
+/// The last character is padded with zero bits.
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    bits: Vec<bool>,
+}
+
+impl BitWriter {
+    pub fn write(&mut self, value: u32, width: u32) {
+        for i in (0..width).rev() {
+            self.bits.push((value >> i) & 1 == 1);
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.bits
+            .chunks(6)
+            .map(|chunk| {
+                let index = chunk
+                    .iter()
+                    .chain(std::iter::repeat(&false))
+                    .take(6)
+                    .fold(0, |acc, bit| (acc << 1) | *bit as usize);
+                CHARACTER_TABLE[index] as char
+            })
+            .collect()
+    }
+}
+
+/// Reads back the values written by `BitWriter` from one section of a data string
+pub(crate) struct BitReader {
+    section: &'static str,
+    bits: Vec<bool>,
+    pos: usize,
+}
+
+impl BitReader {
+    pub fn new(section: &'static str, base64: &str) -> Result<Self, PlanetDataError> {
+        let mut bits = Vec::with_capacity(base64.len() * 6);
+        for character in base64.chars() {
+            let index = CHARACTER_TABLE
+                .iter()
+                .position(|c| *c as char == character)
+                .ok_or(PlanetDataError::InvalidCharacter { section, character })?;
+            for i in (0..6).rev() {
+                bits.push((index >> i) & 1 == 1);
+            }
+        }
+        Ok(BitReader {
+            section,
+            bits,
+            pos: 0,
+        })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bits.len() - self.pos
+    }
+
+    pub fn read(&mut self, width: u32) -> u32 {
+        let value = self.bits[self.pos..self.pos + width as usize]
+            .iter()
+            .fold(0, |acc, bit| (acc << 1) | *bit as u32);
+        self.pos += width as usize;
+        value
+    }
+
+    /// Only the zero bits padding the last character may be left
+    pub fn finish(self) -> Result<(), PlanetDataError> {
+        if self.remaining() >= 6 || self.bits[self.pos..].iter().any(|bit| *bit) {
+            return Err(PlanetDataError::TrailingBits {
+                section: self.section,
+            });
+        }
+        Ok(())
+    }
+}
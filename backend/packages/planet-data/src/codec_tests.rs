@@ -0,0 +1,218 @@
+#![cfg(test)]
+use crate::geo::{GEO_PALETTE_SIZE, UNIQUE_VERTICES};
+use crate::vox::{block_index, BLOCK_COUNT, EMPTY_BLOCK, GRID_SIZE};
+use crate::{
+    diff, Change, Color, GeoPlanet, PlanetData, PlanetDataError, SceneryItem, Vertex, VoxPlanet,
+};
+
+const GEO_FIXTURES: [&str; 7] = [
+    include_str!("../fixtures/geo-default.txt"),
+    include_str!("../fixtures/geo-unminted.txt"),
+    include_str!("../fixtures/geo-example-1.txt"),
+    include_str!("../fixtures/geo-example-2.txt"),
+    include_str!("../fixtures/geo-example-3.txt"),
+    include_str!("../fixtures/geo-example-4.txt"),
+    include_str!("../fixtures/geo-example-5.txt"),
+];
+const VOX_DEFAULT: &str = include_str!("../fixtures/vox-default.txt");
+
+#[test]
+fn fixtures_round_trip() {
+    for fixture in GEO_FIXTURES.iter().chain([VOX_DEFAULT].iter()) {
+        let data = fixture.trim();
+        let planet = PlanetData::decode(data).unwrap();
+        assert_eq!(planet.encode().unwrap(), data);
+
+        // and through the JSON form
+        let json = serde_json::to_string(&planet).unwrap();
+        let parsed: PlanetData = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, planet);
+        assert_eq!(parsed.encode().unwrap(), data);
+    }
+}
+
+#[test]
+fn decode_default_geo_planet() {
+    let planet = GeoPlanet::decode(GEO_FIXTURES[0].trim()).unwrap();
+    assert_eq!(planet.terrain.len(), UNIQUE_VERTICES);
+    assert!(planet.terrain.iter().all(|vertex| *vertex == Vertex::default()));
+    assert_eq!(planet.scenery.model_pack, 0);
+    assert!(planet.scenery.items.is_empty());
+    assert_eq!(planet.palette.len(), GEO_PALETTE_SIZE);
+    assert_eq!(planet.palette[0], Color::new(0x3b, 0x60, 0x10));
+    assert_eq!(planet.palette[7], Color::new(0xde, 0xde, 0xde));
+    assert_eq!(planet.atmosphere.color, Color::new(0x32, 0xa2, 0xa8));
+    assert_eq!(planet.atmosphere.height, 0x84);
+    assert_eq!(planet.atmosphere.density, 0x7d);
+    assert_eq!(planet.water.color, Color::new(0x32, 0xa2, 0xa8));
+    assert_eq!(planet.water.height, 0x73);
+    assert_eq!(planet.water.density, 0x80);
+}
+
+#[test]
+fn decode_geo_scenery() {
+    let planet = GeoPlanet::decode(GEO_FIXTURES[1].trim()).unwrap();
+    // 6 model pack bits and 20 bits per item in 61 characters
+    assert_eq!(planet.scenery.items.len(), (61 * 6 - 6) / 20);
+    assert!(planet
+        .scenery
+        .items
+        .iter()
+        .all(|item| item.location_id < 1922 && item.color_id < 8));
+}
+
+#[test]
+fn decode_default_vox_planet() {
+    let planet = VoxPlanet::decode(VOX_DEFAULT.trim()).unwrap();
+    assert_eq!(planet.blocks.len(), BLOCK_COUNT);
+    // a ball of blocks centered in the grid
+    let center = GRID_SIZE / 2;
+    assert_ne!(planet.block(center, center, center), EMPTY_BLOCK);
+    assert_eq!(planet.block(0, 0, 0), EMPTY_BLOCK);
+    assert_eq!(planet.block(GRID_SIZE - 1, GRID_SIZE - 1, GRID_SIZE - 1), EMPTY_BLOCK);
+    let solid = planet.blocks.iter().filter(|block| **block != EMPTY_BLOCK).count();
+    assert!(solid > 0 && solid < BLOCK_COUNT);
+    assert!(planet.scenery.items.is_empty());
+    assert_eq!(planet.palette.len(), 15);
+}
+
+#[test]
+fn edits_survive_a_round_trip() {
+    let mut planet = VoxPlanet::decode(VOX_DEFAULT.trim()).unwrap();
+    planet.set_block(1, 2, 3, 7);
+    planet.scenery.items.push(SceneryItem {
+        object_id: 3,
+        location_id: (block_index(1, 2, 3) * 6 + 2) as u16,
+        color_id: 14,
+    });
+    planet.palette[14] = Color::new(1, 2, 3);
+    let decoded = VoxPlanet::decode(&planet.encode().unwrap()).unwrap();
+    assert_eq!(decoded, planet);
+
+    let mut planet = GeoPlanet::decode(GEO_FIXTURES[2].trim()).unwrap();
+    planet.terrain[641] = Vertex {
+        height: 255,
+        color: 7,
+    };
+    planet.water.density = 0;
+    let decoded = GeoPlanet::decode(&planet.encode().unwrap()).unwrap();
+    assert_eq!(decoded, planet);
+}
+
+#[test]
+fn invalid_data_is_rejected() {
+    let data = GEO_FIXTURES[0].trim();
+
+    let err = PlanetData::decode("PLANET=abc").unwrap_err();
+    assert_eq!(
+        err,
+        PlanetDataError::UnknownFormat {
+            format: "PLANET".to_string()
+        }
+    );
+
+    let err = PlanetData::decode(&format!("{}=AAAAAAA", data)).unwrap_err();
+    assert_eq!(
+        err,
+        PlanetDataError::SectionCount {
+            expected: 6,
+            found: 7
+        }
+    );
+
+    let err = PlanetData::decode(&data.replacen("gBAC", "gBA-", 1)).unwrap_err();
+    assert_eq!(
+        err,
+        PlanetDataError::InvalidCharacter {
+            section: "terrain",
+            character: '-'
+        }
+    );
+
+    let err = PlanetData::decode(&data.replacen("gBAC", "gBA", 1)).unwrap_err();
+    assert_eq!(
+        err,
+        PlanetDataError::SectionLength {
+            section: "terrain",
+            expected: 1177,
+            found: 1176
+        }
+    );
+
+    // the last water character only has 4 data bits
+    let err = PlanetData::decode(&format!("{}B", &data[..data.len() - 1])).unwrap_err();
+    assert_eq!(err, PlanetDataError::TrailingBits { section: "water" });
+
+    // scenery location past the last face
+    let mut planet = GeoPlanet::decode(data).unwrap();
+    planet.scenery.items.push(SceneryItem {
+        object_id: 0,
+        location_id: 1922,
+        color_id: 0,
+    });
+    let err = planet.encode().unwrap_err();
+    assert_eq!(
+        err,
+        PlanetDataError::OutOfRange {
+            what: "Scenery location",
+            index: 0,
+            value: 1922,
+            max: 1921
+        }
+    );
+
+    let mut planet = VoxPlanet::decode(VOX_DEFAULT.trim()).unwrap();
+    planet.palette.pop();
+    let err = planet.encode().unwrap_err();
+    assert_eq!(
+        err,
+        PlanetDataError::Count {
+            what: "palette colors",
+            expected: 15,
+            found: 14
+        }
+    );
+}
+
+#[test]
+fn diff_lists_changed_values() {
+    let old = PlanetData::decode(GEO_FIXTURES[0].trim()).unwrap();
+    assert!(diff(&old, &old).is_empty());
+
+    let mut planet = GeoPlanet::decode(GEO_FIXTURES[0].trim()).unwrap();
+    planet.terrain[5].height = 200;
+    planet.palette[2] = Color::new(0, 0, 0);
+    planet.water.density = 3;
+    planet.scenery.items.push(SceneryItem {
+        object_id: 1,
+        location_id: 700,
+        color_id: 4,
+    });
+    let new = PlanetData::Geo(planet);
+    let changes: Vec<String> = diff(&old, &new).iter().map(|c| c.to_string()).collect();
+    assert_eq!(
+        changes,
+        vec![
+            "terrain[5].height: 128 -> 200",
+            "scenery[location 700]: - -> object 1 color 4",
+            "palette[2]: #edd6bb -> #000000",
+            "water.density: 128 -> 3",
+        ]
+    );
+
+    let mut planet = VoxPlanet::decode(VOX_DEFAULT.trim()).unwrap();
+    let vox_old = PlanetData::Vox(planet.clone());
+    planet.set_block(0, 1, 2, 4);
+    let changes = diff(&vox_old, &PlanetData::Vox(planet));
+    assert_eq!(
+        changes,
+        vec![Change {
+            path: "blocks[0,1,2]".to_string(),
+            old: Some("15".to_string()),
+            new: Some("4".to_string()),
+        }]
+    );
+
+    let changes = diff(&old, &vox_old);
+    assert_eq!(changes[0].path, "format");
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::PlanetDataError;
+
+/// Characters used by one color in a palette section
+pub const SERIALIZED_COLOR_SIZE: usize = 4;
+
+/// 8 bit RGB color, written as "#rrggbb" in JSON
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Color::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    pub(crate) fn write(&self, writer: &mut BitWriter) {
+        writer.write(self.r as u32, 8);
+        writer.write(self.g as u32, 8);
+        writer.write(self.b as u32, 8);
+    }
+
+    pub(crate) fn read(reader: &mut BitReader) -> Self {
+        let r = reader.read(8) as u8;
+        let g = reader.read(8) as u8;
+        let b = reader.read(8) as u8;
+        Color::new(r, g, b)
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_hex()
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(hex: String) -> Result<Self, Self::Error> {
+        Color::from_hex(&hex).ok_or(format!("Invalid color {:?}, expected #rrggbb", hex))
+    }
+}
+
+pub(crate) fn encode_palette(palette: &[Color], size: usize) -> Result<String, PlanetDataError> {
+    if palette.len() != size {
+        return Err(PlanetDataError::Count {
+            what: "palette colors",
+            expected: size,
+            found: palette.len(),
+        });
+    }
+    let mut writer = BitWriter::default();
+    palette.iter().for_each(|color| color.write(&mut writer));
+    Ok(writer.finish())
+}
+
+pub(crate) fn decode_palette(base64: &str, size: usize) -> Result<Vec<Color>, PlanetDataError> {
+    if base64.len() != size * SERIALIZED_COLOR_SIZE {
+        return Err(PlanetDataError::SectionLength {
+            section: "palette",
+            expected: size * SERIALIZED_COLOR_SIZE,
+            found: base64.len(),
+        });
+    }
+    let mut reader = BitReader::new("palette", base64)?;
+    let palette = (0..size).map(|_| Color::read(&mut reader)).collect();
+    reader.finish()?;
+    Ok(palette)
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use crate::geo::{GeoPlanet, Layer};
+use crate::scenery::{Scenery, SceneryItem};
+use crate::vox::{VoxPlanet, GRID_SIZE};
+use crate::{Color, PlanetData};
+
+/// One difference between two revisions of a planet, `None` when the value does not exist
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    /// Changed value, like "terrain[12].height" or "blocks[3,4,5]"
+    pub path: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+        write!(f, "{}: {} -> {}", self.path, show(&self.old), show(&self.new))
+    }
+}
+
+/// Lists what changed from `old` to `new`
+pub fn diff(old: &PlanetData, new: &PlanetData) -> Vec<Change> {
+    let mut changes = Changes::default();
+    match (old, new) {
+        (PlanetData::Geo(old), PlanetData::Geo(new)) => diff_geo(&mut changes, old, new),
+        (PlanetData::Vox(old), PlanetData::Vox(new)) => diff_vox(&mut changes, old, new),
+        (PlanetData::Geo(_), _) => changes.push("format", "GEO1", "VOX1"),
+        (PlanetData::Vox(_), _) => changes.push("format", "VOX1", "GEO1"),
+    }
+    changes.0
+}
+
+#[derive(Default)]
+struct Changes(Vec<Change>);
+
+impl Changes {
+    fn push<T: ToString>(&mut self, path: &str, old: T, new: T) {
+        self.0.push(Change {
+            path: path.to_string(),
+            old: Some(old.to_string()),
+            new: Some(new.to_string()),
+        });
+    }
+
+    fn compare<T: ToString + PartialEq>(&mut self, path: &str, old: T, new: T) {
+        if old != new {
+            self.push(path, old, new);
+        }
+    }
+}
+
+fn diff_geo(changes: &mut Changes, old: &GeoPlanet, new: &GeoPlanet) {
+    for (index, (old, new)) in old.terrain.iter().zip(&new.terrain).enumerate() {
+        changes.compare(&format!("terrain[{}].height", index), old.height, new.height);
+        changes.compare(&format!("terrain[{}].color", index), old.color, new.color);
+    }
+    diff_scenery(changes, &old.scenery, &new.scenery);
+    diff_palette(changes, &old.palette, &new.palette);
+    diff_layer(changes, "atmosphere", &old.atmosphere, &new.atmosphere);
+    diff_layer(changes, "water", &old.water, &new.water);
+}
+
+fn diff_vox(changes: &mut Changes, old: &VoxPlanet, new: &VoxPlanet) {
+    for y in 0..GRID_SIZE {
+        for z in 0..GRID_SIZE {
+            for x in 0..GRID_SIZE {
+                let path = format!("blocks[{},{},{}]", x, y, z);
+                changes.compare(&path, old.block(x, y, z), new.block(x, y, z));
+            }
+        }
+    }
+    diff_scenery(changes, &old.scenery, &new.scenery);
+    diff_palette(changes, &old.palette, &new.palette);
+}
+
+fn diff_palette(changes: &mut Changes, old: &[Color], new: &[Color]) {
+    for (index, (old, new)) in old.iter().zip(new).enumerate() {
+        changes.compare(&format!("palette[{}]", index), old.to_hex(), new.to_hex());
+    }
+}
+
+fn diff_layer(changes: &mut Changes, name: &str, old: &Layer, new: &Layer) {
+    changes.compare(&format!("{}.color", name), old.color.to_hex(), new.color.to_hex());
+    changes.compare(&format!("{}.height", name), old.height, new.height);
+    changes.compare(&format!("{}.density", name), old.density, new.density);
+}
+
+/// Scenery items are matched by location, the frontend keeps one item per location
+fn diff_scenery(changes: &mut Changes, old: &Scenery, new: &Scenery) {
+    changes.compare("scenery.model_pack", old.model_pack, new.model_pack);
+    let by_location = |scenery: &Scenery| -> BTreeMap<u16, String> {
+        scenery
+            .items
+            .iter()
+            .map(|item| (item.location_id, describe_item(item)))
+            .collect()
+    };
+    let old_items = by_location(old);
+    let new_items = by_location(new);
+    let mut locations: Vec<&u16> = old_items.keys().chain(new_items.keys()).collect();
+    locations.sort();
+    locations.dedup();
+    for location in locations {
+        let old = old_items.get(location);
+        let new = new_items.get(location);
+        if old != new {
+            changes.0.push(Change {
+                path: format!("scenery[location {}]", location),
+                old: old.cloned(),
+                new: new.cloned(),
+            });
+        }
+    }
+}
+
+fn describe_item(item: &SceneryItem) -> String {
+    format!("object {} color {}", item.object_id, item.color_id)
+}
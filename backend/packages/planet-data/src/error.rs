@@ -0,0 +1,59 @@
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PlanetDataError {
+    #[error("Unknown planet format {format:?}")]
+    UnknownFormat { format: String },
+
+    #[error("Expected {expected} sections, found {found}")]
+    SectionCount { expected: usize, found: usize },
+
+    #[error("Invalid base64 character {character:?} in the {section} section")]
+    InvalidCharacter {
+        section: &'static str,
+        character: char,
+    },
+
+    #[error("The {section} section must be {expected} characters long, found {found}")]
+    SectionLength {
+        section: &'static str,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("The {section} section is empty")]
+    EmptySection { section: &'static str },
+
+    #[error("The {section} section has unused trailing bits")]
+    TrailingBits { section: &'static str },
+
+    #[error("Expected {expected} {what}, found {found}")]
+    Count {
+        what: &'static str,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("{what} {index} is out of range: {value} is more than {max}")]
+    OutOfRange {
+        what: &'static str,
+        index: usize,
+        value: u32,
+        max: u32,
+    },
+
+    #[error("Scenery holds {found} items, at most {max} are allowed")]
+    TooMuchScenery { found: usize, max: usize },
+
+    #[error("Invalid MagicaVoxel model: {reason}")]
+    InvalidModel { reason: String },
+
+    #[error("The file holds {count} models, only single model files can be imported")]
+    MultipleModels { count: usize },
+
+    #[error("The model has no voxels")]
+    EmptyModel,
+
+    #[error("The model is {size} blocks once trimmed, planets are {max} blocks a side, crop or scale it")]
+    ModelTooLarge { size: String, max: usize },
+}
@@ -0,0 +1,202 @@
+use std::fmt::Write;
+
+use serde_json::json;
+
+use crate::vox::{VoxPlanet, EMPTY_BLOCK, GRID_SIZE};
+use crate::{Color, Mesh};
+
+/// Version written in the header of MagicaVoxel files
+pub const MAGICA_VOXEL_VERSION: u32 = 150;
+/// Entries of a MagicaVoxel palette, color index `i` is entry `i - 1`
+pub const MAGICA_VOXEL_PALETTE_SIZE: usize = 256;
+
+const GLB_MAGIC: u32 = 0x4654_6c67;
+const GLB_JSON_CHUNK: u32 = 0x4e4f_534a;
+const GLB_BIN_CHUNK: u32 = 0x004e_4942;
+const GLTF_FLOAT: u32 = 5126;
+const GLTF_UNSIGNED_INT: u32 = 5125;
+const GLTF_ARRAY_BUFFER: u32 = 34962;
+const GLTF_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Position of block (x, y, z) in a MagicaVoxel model, which points z up
+/// instead of y and keeps the grid right handed
+pub fn magica_voxel_position(x: usize, y: usize, z: usize) -> [u8; 3] {
+    [x as u8, (GRID_SIZE - 1 - z) as u8, y as u8]
+}
+
+/// Writes the planet as a MagicaVoxel `.vox` model, block value `b` using color index `b + 1`
+pub fn to_magica_voxel(planet: &VoxPlanet) -> Vec<u8> {
+    let mut size = vec![];
+    for _ in 0..3 {
+        size.extend_from_slice(&(GRID_SIZE as u32).to_le_bytes());
+    }
+
+    let mut voxels = vec![];
+    for x in 0..GRID_SIZE {
+        for y in 0..GRID_SIZE {
+            for z in 0..GRID_SIZE {
+                let block = planet.block(x, y, z);
+                if block != EMPTY_BLOCK {
+                    voxels.extend_from_slice(&magica_voxel_position(x, y, z));
+                    voxels.push(block + 1);
+                }
+            }
+        }
+    }
+    let mut xyzi = ((voxels.len() / 4) as u32).to_le_bytes().to_vec();
+    xyzi.extend_from_slice(&voxels);
+
+    let mut rgba = vec![];
+    for index in 0..MAGICA_VOXEL_PALETTE_SIZE {
+        let color = planet.palette.get(index).copied().unwrap_or_default();
+        rgba.extend_from_slice(&[color.r, color.g, color.b, 255]);
+    }
+
+    let mut children = vec![];
+    write_vox_chunk(&mut children, b"SIZE", &size, &[]);
+    write_vox_chunk(&mut children, b"XYZI", &xyzi, &[]);
+    write_vox_chunk(&mut children, b"RGBA", &rgba, &[]);
+
+    let mut file = b"VOX ".to_vec();
+    file.extend_from_slice(&MAGICA_VOXEL_VERSION.to_le_bytes());
+    write_vox_chunk(&mut file, b"MAIN", &[], &children);
+    file
+}
+
+fn write_vox_chunk(file: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    file.extend_from_slice(id);
+    file.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    file.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    file.extend_from_slice(content);
+    file.extend_from_slice(children);
+}
+
+/// Writes the mesh as a Wavefront OBJ, vertex colors follow the positions as
+/// `v x y z r g b` which Blender and MeshLab read
+pub fn to_obj(mesh: &Mesh) -> String {
+    let mut obj = String::new();
+    for (position, color) in mesh.positions.iter().zip(mesh.colors.iter()) {
+        let [r, g, b] = srgb(*color);
+        writeln!(
+            obj,
+            "v {:.6} {:.6} {:.6} {:.6} {:.6} {:.6}",
+            position[0], position[1], position[2], r, g, b
+        )
+        .unwrap();
+    }
+    for [a, b, c] in &mesh.triangles {
+        writeln!(obj, "f {} {} {}", a + 1, b + 1, c + 1).unwrap();
+    }
+    obj
+}
+
+/// Writes the mesh as a binary glTF (`.glb`) with a `COLOR_0` vertex attribute
+pub fn to_gltf(mesh: &Mesh) -> Vec<u8> {
+    let mut buffer = vec![];
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for position in &mesh.positions {
+        for axis in 0..3 {
+            let value = position[axis] as f32;
+            min[axis] = min[axis].min(value);
+            max[axis] = max[axis].max(value);
+            buffer.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    let positions_length = buffer.len();
+    for color in &mesh.colors {
+        for channel in linear(*color).iter() {
+            buffer.extend_from_slice(&(*channel as f32).to_le_bytes());
+        }
+    }
+    let colors_length = buffer.len() - positions_length;
+    for index in mesh.triangles.iter().flatten() {
+        buffer.extend_from_slice(&(*index as u32).to_le_bytes());
+    }
+    let indices_length = buffer.len() - positions_length - colors_length;
+    if mesh.positions.is_empty() {
+        min = [0.0; 3];
+        max = [0.0; 3];
+    }
+
+    let gltf = json!({
+        "asset": { "version": "2.0", "generator": "planet-data" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0 }],
+        "meshes": [{
+            "primitives": [{
+                "attributes": { "POSITION": 0, "COLOR_0": 1 },
+                "indices": 2,
+                "mode": 4,
+            }],
+        }],
+        "buffers": [{ "byteLength": buffer.len() }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": positions_length, "target": GLTF_ARRAY_BUFFER },
+            { "buffer": 0, "byteOffset": positions_length, "byteLength": colors_length, "target": GLTF_ARRAY_BUFFER },
+            {
+                "buffer": 0,
+                "byteOffset": positions_length + colors_length,
+                "byteLength": indices_length,
+                "target": GLTF_ELEMENT_ARRAY_BUFFER,
+            },
+        ],
+        "accessors": [
+            {
+                "bufferView": 0,
+                "componentType": GLTF_FLOAT,
+                "count": mesh.positions.len(),
+                "type": "VEC3",
+                "min": min,
+                "max": max,
+            },
+            { "bufferView": 1, "componentType": GLTF_FLOAT, "count": mesh.colors.len(), "type": "VEC3" },
+            {
+                "bufferView": 2,
+                "componentType": GLTF_UNSIGNED_INT,
+                "count": mesh.triangles.len() * 3,
+                "type": "SCALAR",
+            },
+        ],
+    });
+
+    let mut json = gltf.to_string().into_bytes();
+    pad(&mut json, b' ');
+    pad(&mut buffer, 0);
+    let length = 12 + 8 + json.len() + 8 + buffer.len();
+
+    let mut glb = vec![];
+    glb.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(length as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_JSON_CHUNK.to_le_bytes());
+    glb.extend_from_slice(&json);
+    glb.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_BIN_CHUNK.to_le_bytes());
+    glb.extend_from_slice(&buffer);
+    glb
+}
+
+/// glTF chunks are aligned on 4 bytes
+fn pad(chunk: &mut Vec<u8>, filler: u8) {
+    while chunk.len() % 4 != 0 {
+        chunk.push(filler);
+    }
+}
+
+fn srgb(color: Color) -> [f64; 3] {
+    [color.r, color.g, color.b].map(|channel| f64::from(channel) / 255.0)
+}
+
+/// glTF vertex colors are linear rather than sRGB
+fn linear(color: Color) -> [f64; 3] {
+    srgb(color).map(|channel| {
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
@@ -0,0 +1,110 @@
+#![cfg(test)]
+use std::convert::TryInto;
+
+use crate::export::{magica_voxel_position, to_gltf, to_magica_voxel, to_obj};
+use crate::geo::{UNIQUE_FACES, UNIQUE_VERTICES};
+use crate::vox::{BLOCK_COUNT, EMPTY_BLOCK};
+use crate::{Color, GeoPlanet, VoxPlanet};
+
+const GEO_EXAMPLE: &str = include_str!("../fixtures/geo-example-1.txt");
+const VOX_DEFAULT: &str = include_str!("../fixtures/vox-default.txt");
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// Content of the chunks nested in the MAIN chunk of a .vox file
+fn vox_chunks(file: &[u8]) -> Vec<(String, Vec<u8>)> {
+    assert_eq!(&file[..4], b"VOX ");
+    assert_eq!(read_u32(file, 4), 150);
+    assert_eq!(&file[8..12], b"MAIN");
+    assert_eq!(read_u32(file, 16) as usize, file.len() - 20);
+    let mut chunks = vec![];
+    let mut offset = 20;
+    while offset < file.len() {
+        let length = read_u32(file, offset + 4) as usize;
+        let id = String::from_utf8(file[offset..offset + 4].to_vec()).unwrap();
+        chunks.push((id, file[offset + 12..offset + 12 + length].to_vec()));
+        offset += 12 + length;
+    }
+    chunks
+}
+
+#[test]
+fn magica_voxel_keeps_blocks_and_palette() {
+    let planet = VoxPlanet::decode(VOX_DEFAULT.trim()).unwrap();
+    let chunks = vox_chunks(&to_magica_voxel(&planet));
+    let ids: Vec<&str> = chunks.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, vec!["SIZE", "XYZI", "RGBA"]);
+    assert_eq!(chunks[0].1, [16, 0, 0, 0, 16, 0, 0, 0, 16, 0, 0, 0]);
+
+    let xyzi = &chunks[1].1;
+    let filled = planet.blocks.iter().filter(|block| **block != EMPTY_BLOCK).count();
+    assert_eq!(read_u32(xyzi, 0) as usize, filled);
+    assert_eq!(xyzi.len(), 4 + filled * 4);
+
+    let rgba = &chunks[2].1;
+    assert_eq!(rgba.len(), 256 * 4);
+    for (index, color) in planet.palette.iter().enumerate() {
+        assert_eq!(rgba[index * 4..index * 4 + 4], [color.r, color.g, color.b, 255]);
+    }
+}
+
+#[test]
+fn magica_voxel_points_z_up() {
+    let mut planet = VoxPlanet::decode(VOX_DEFAULT.trim()).unwrap();
+    planet.blocks = vec![EMPTY_BLOCK; BLOCK_COUNT];
+    planet.set_block(1, 2, 3, 0);
+    planet.set_block(4, 5, 6, 14);
+    let chunks = vox_chunks(&to_magica_voxel(&planet));
+    assert_eq!(magica_voxel_position(1, 2, 3), [1, 12, 2]);
+    assert_eq!(chunks[1].1, [2, 0, 0, 0, 1, 12, 2, 1, 4, 9, 5, 15]);
+}
+
+#[test]
+fn obj_lists_colored_vertices_and_faces() {
+    let planet = GeoPlanet::decode(GEO_EXAMPLE.trim()).unwrap();
+    let obj = to_obj(&planet.terrain_mesh());
+    let vertices: Vec<&str> = obj.lines().filter(|line| line.starts_with("v ")).collect();
+    let faces: Vec<&str> = obj.lines().filter(|line| line.starts_with("f ")).collect();
+    assert_eq!(vertices.len(), UNIQUE_VERTICES);
+    assert_eq!(faces.len(), UNIQUE_FACES);
+    assert_eq!(faces[0], "f 1 2 3");
+
+    for (line, vertex) in vertices.iter().zip(planet.terrain.iter()) {
+        let values: Vec<f64> = line[2..].split(' ').map(|v| v.parse().unwrap()).collect();
+        let color = planet.palette[vertex.color as usize];
+        let rgb = [color.r, color.g, color.b].map(|c| f64::from(c) / 255.0);
+        for channel in 0..3 {
+            assert!((values[3 + channel] - rgb[channel]).abs() < 1e-6);
+        }
+        let radius = (values[0].powi(2) + values[1].powi(2) + values[2].powi(2)).sqrt();
+        assert!((radius - (1.0 + f64::from(vertex.height) / 128.0)).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn gltf_holds_the_mesh() {
+    let mut planet = GeoPlanet::decode(GEO_EXAMPLE.trim()).unwrap();
+    planet.palette[planet.terrain[0].color as usize] = Color::new(255, 0, 0);
+    let glb = to_gltf(&planet.terrain_mesh());
+    assert_eq!(&glb[..4], b"glTF");
+    assert_eq!(read_u32(&glb, 4), 2);
+    assert_eq!(read_u32(&glb, 8) as usize, glb.len());
+
+    let json_length = read_u32(&glb, 12) as usize;
+    assert_eq!(&glb[16..20], b"JSON");
+    let gltf: serde_json::Value = serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+    let accessors = gltf["accessors"].as_array().unwrap();
+    assert_eq!(accessors[0]["count"], UNIQUE_VERTICES);
+    assert_eq!(accessors[1]["count"], UNIQUE_VERTICES);
+    assert_eq!(accessors[2]["count"], UNIQUE_FACES * 3);
+
+    let bin = &glb[20 + json_length + 8..];
+    assert_eq!(&glb[20 + json_length + 4..20 + json_length + 8], b"BIN\0");
+    let colors = gltf["bufferViews"][1]["byteOffset"].as_u64().unwrap() as usize;
+    let first_color: Vec<f32> = (0..3)
+        .map(|i| f32::from_le_bytes(bin[colors + i * 4..colors + i * 4 + 4].try_into().unwrap()))
+        .collect();
+    assert_eq!(first_color, vec![1.0, 0.0, 0.0]);
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::color::{decode_palette, encode_palette, Color};
+use crate::error::PlanetDataError;
+use crate::scenery::{check_range, Scenery, SceneryLayout};
+use crate::split_sections;
+
+pub const GEO_FORMAT: &str = "GEO1";
+/// Vertices of the subdivided icosahedron the terrain is built on
+pub const UNIQUE_VERTICES: usize = 642;
+pub const UNIQUE_FACES: usize = 1280;
+pub const GEO_PALETTE_SIZE: usize = 8;
+/// Vertex heights run from 0 to `HEIGHT_PRECISION - 1`, the default is half way
+pub const HEIGHT_PRECISION: u32 = 256;
+pub const MAX_GEO_SCENERY: usize = 512;
+
+const HEIGHT_BITS: u32 = 8;
+const VERTEX_COLOR_BITS: u32 = 3;
+const VERTEX_BITS: usize = (HEIGHT_BITS + VERTEX_COLOR_BITS) as usize;
+const TERRAIN_SIZE: usize = UNIQUE_VERTICES * VERTEX_BITS / 6;
+const LAYER_SIZE: usize = 7;
+
+/// Scenery locations below `UNIQUE_VERTICES` are vertices, the next ones are faces
+const SCENERY_LAYOUT: SceneryLayout = SceneryLayout {
+    location_bits: 11,
+    color_bits: 3,
+    locations: (UNIQUE_VERTICES + UNIQUE_FACES) as u32,
+    colors: GEO_PALETTE_SIZE as u32,
+    max_items: MAX_GEO_SCENERY,
+};
+
+/// Geodesic planet, data string "GEO1=terrain=scenery=palette=atmosphere=water"
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoPlanet {
+    /// One entry per icosahedron vertex
+    pub terrain: Vec<Vertex>,
+    pub scenery: Scenery,
+    pub palette: Vec<Color>,
+    pub atmosphere: Layer,
+    pub water: Layer,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Vertex {
+    pub height: u8,
+    /// Palette index of the ground color
+    pub color: u8,
+}
+
+/// Atmosphere or water shell around the planet
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Layer {
+    pub color: Color,
+    pub height: u8,
+    pub density: u8,
+}
+
+impl Default for Vertex {
+    fn default() -> Self {
+        Vertex {
+            height: (HEIGHT_PRECISION / 2) as u8,
+            color: 0,
+        }
+    }
+}
+
+impl GeoPlanet {
+    pub fn validate(&self) -> Result<(), PlanetDataError> {
+        if self.terrain.len() != UNIQUE_VERTICES {
+            return Err(PlanetDataError::Count {
+                what: "terrain vertices",
+                expected: UNIQUE_VERTICES,
+                found: self.terrain.len(),
+            });
+        }
+        for (index, vertex) in self.terrain.iter().enumerate() {
+            check_range("Vertex color", index, vertex.color as u32, GEO_PALETTE_SIZE as u32 - 1)?;
+        }
+        if self.palette.len() != GEO_PALETTE_SIZE {
+            return Err(PlanetDataError::Count {
+                what: "palette colors",
+                expected: GEO_PALETTE_SIZE,
+                found: self.palette.len(),
+            });
+        }
+        self.scenery.validate(&SCENERY_LAYOUT)
+    }
+
+    pub fn encode(&self) -> Result<String, PlanetDataError> {
+        self.validate()?;
+        let mut terrain = BitWriter::default();
+        for vertex in &self.terrain {
+            terrain.write(vertex.height as u32, HEIGHT_BITS);
+            terrain.write(vertex.color as u32, VERTEX_COLOR_BITS);
+        }
+        Ok([
+            GEO_FORMAT.to_string(),
+            terrain.finish(),
+            self.scenery.encode(&SCENERY_LAYOUT)?,
+            encode_palette(&self.palette, GEO_PALETTE_SIZE)?,
+            self.atmosphere.encode(),
+            self.water.encode(),
+        ]
+        .join("="))
+    }
+
+    pub fn decode(data: &str) -> Result<Self, PlanetDataError> {
+        let sections = split_sections(data, GEO_FORMAT, 6)?;
+
+        check_length("terrain", sections[1], TERRAIN_SIZE)?;
+        let mut reader = BitReader::new("terrain", sections[1])?;
+        let terrain = (0..UNIQUE_VERTICES)
+            .map(|_| Vertex {
+                height: reader.read(HEIGHT_BITS) as u8,
+                color: reader.read(VERTEX_COLOR_BITS) as u8,
+            })
+            .collect();
+        reader.finish()?;
+
+        Ok(GeoPlanet {
+            terrain,
+            scenery: Scenery::decode(sections[2], &SCENERY_LAYOUT)?,
+            palette: decode_palette(sections[3], GEO_PALETTE_SIZE)?,
+            atmosphere: Layer::decode("atmosphere", sections[4])?,
+            water: Layer::decode("water", sections[5])?,
+        })
+    }
+}
+
+impl Layer {
+    fn encode(&self) -> String {
+        let mut writer = BitWriter::default();
+        self.color.write(&mut writer);
+        writer.write(self.height as u32, 8);
+        writer.write(self.density as u32, 8);
+        writer.finish()
+    }
+
+    fn decode(section: &'static str, base64: &str) -> Result<Self, PlanetDataError> {
+        check_length(section, base64, LAYER_SIZE)?;
+        let mut reader = BitReader::new(section, base64)?;
+        let layer = Layer {
+            color: Color::read(&mut reader),
+            height: reader.read(8) as u8,
+            density: reader.read(8) as u8,
+        };
+        reader.finish()?;
+        Ok(layer)
+    }
+}
+
+pub(crate) fn check_length(
+    section: &'static str,
+    base64: &str,
+    expected: usize,
+) -> Result<(), PlanetDataError> {
+    if base64.len() != expected {
+        return Err(PlanetDataError::SectionLength {
+            section,
+            expected,
+            found: base64.len(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::convert::TryInto;
+
+use crate::vox::{block_index, VoxPlanet, BLOCK_COUNT, EMPTY_BLOCK, GRID_SIZE, VOX_PALETTE_SIZE};
+use crate::{Color, PlanetDataError, Scenery};
+
+/// What to do with models larger than the VOX1 grid once their empty space is trimmed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fit {
+    /// Refuse the model
+    Reject,
+    /// Keep the middle of the model, cutting off what sticks out
+    Crop,
+    /// Shrink the whole model to fit
+    Scale,
+}
+
+/// Voxels of a model in planet axes, y pointing up
+struct Model {
+    size: [usize; 3],
+    voxels: Vec<([usize; 3], u8)>,
+}
+
+/// Reads a single model MagicaVoxel `.vox` file into a VOX1 planet.
+/// The palette is reduced to the 15 planet colors, models up to 16 blocks a
+/// side are centered in the grid, larger ones are trimmed then handled by `fit`
+pub fn from_magica_voxel(file: &[u8], fit: Fit) -> Result<VoxPlanet, PlanetDataError> {
+    let (model, palette) = read_magica_voxel(file)?;
+    if model.voxels.is_empty() {
+        return Err(PlanetDataError::EmptyModel);
+    }
+    let model = fit_model(model, fit)?;
+
+    let mut counts = HashMap::new();
+    for (_, color) in &model.voxels {
+        *counts.entry(*color).or_insert(0usize) += 1;
+    }
+    let mut used: Vec<(u8, usize)> = counts.into_iter().collect();
+    used.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let colors: Vec<(Color, usize)> = used
+        .iter()
+        .map(|(index, count)| (palette[*index as usize - 1], *count))
+        .collect();
+    let planet_palette = quantize(&colors, VOX_PALETTE_SIZE);
+    let block_of: HashMap<u8, u8> = used
+        .iter()
+        .map(|(index, _)| (*index, nearest(&planet_palette, palette[*index as usize - 1])))
+        .collect();
+
+    let offset = model.size.map(|size| (GRID_SIZE - size) / 2);
+    let mut blocks = vec![EMPTY_BLOCK; BLOCK_COUNT];
+    for ([x, y, z], color) in &model.voxels {
+        blocks[block_index(x + offset[0], y + offset[1], z + offset[2])] = block_of[color];
+    }
+    let mut palette = planet_palette;
+    palette.resize(VOX_PALETTE_SIZE, Color::default());
+    Ok(VoxPlanet {
+        blocks,
+        scenery: Scenery::default(),
+        palette,
+    })
+}
+
+fn invalid(reason: &str) -> PlanetDataError {
+    PlanetDataError::InvalidModel {
+        reason: reason.to_string(),
+    }
+}
+
+/// Model and RGBA palette of a .vox file, palette entry `i - 1` being color index `i`
+fn read_magica_voxel(file: &[u8]) -> Result<(Model, Vec<Color>), PlanetDataError> {
+    if file.len() < 8 || &file[..4] != b"VOX " {
+        return Err(invalid("not a MagicaVoxel file"));
+    }
+    let main = read_chunk(file, 8)?;
+    if main.id != *b"MAIN" {
+        return Err(invalid("the MAIN chunk is missing"));
+    }
+
+    let mut sizes = vec![];
+    let mut models = vec![];
+    let mut palette = None;
+    let mut offset = 0;
+    while offset < main.children.len() {
+        let chunk = read_chunk(main.children, offset)?;
+        match &chunk.id {
+            b"SIZE" if chunk.content.len() >= 12 => sizes.push([0, 4, 8].map(|at| {
+                u32::from_le_bytes(chunk.content[at..at + 4].try_into().unwrap()) as usize
+            })),
+            b"XYZI" => models.push(chunk.content),
+            b"RGBA" if chunk.content.len() >= 1024 => {
+                palette = Some(
+                    chunk.content[..1020]
+                        .chunks(4)
+                        .map(|rgba| Color::new(rgba[0], rgba[1], rgba[2]))
+                        .collect::<Vec<_>>(),
+                )
+            }
+            _ => {}
+        }
+        offset = chunk.end;
+    }
+
+    if models.len() > 1 {
+        return Err(PlanetDataError::MultipleModels {
+            count: models.len(),
+        });
+    }
+    let (size, xyzi) = match (sizes.first(), models.first()) {
+        (Some(size), Some(xyzi)) => (*size, *xyzi),
+        _ => return Err(invalid("the file holds no model")),
+    };
+    let palette = palette.ok_or_else(|| invalid("the RGBA palette chunk is missing"))?;
+
+    let count = xyzi
+        .get(..4)
+        .map(|count| u32::from_le_bytes(count.try_into().unwrap()) as usize)
+        .ok_or_else(|| invalid("the XYZI chunk is truncated"))?;
+    if xyzi.len() < 4 + count * 4 {
+        return Err(invalid("the XYZI chunk is truncated"));
+    }
+    let mut voxels = vec![];
+    for voxel in xyzi[4..4 + count * 4].chunks(4) {
+        let [x, y, z, color] = [voxel[0], voxel[1], voxel[2], voxel[3]].map(usize::from);
+        if x >= size[0] || y >= size[1] || z >= size[2] {
+            return Err(invalid("a voxel lies outside the model size"));
+        }
+        if color == 0 {
+            continue;
+        }
+        // MagicaVoxel points z up, planets point y up
+        voxels.push(([x, z, size[1] - 1 - y], color as u8));
+    }
+    let model = Model {
+        size: [size[0], size[2], size[1]],
+        voxels,
+    };
+    Ok((model, palette))
+}
+
+struct Chunk<'a> {
+    id: [u8; 4],
+    content: &'a [u8],
+    children: &'a [u8],
+    end: usize,
+}
+
+fn read_chunk(data: &[u8], offset: usize) -> Result<Chunk<'_>, PlanetDataError> {
+    let truncated = || invalid("a chunk is truncated");
+    let header = data.get(offset..offset + 12).ok_or_else(truncated)?;
+    let content_length = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+    let children_length = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+    let content_end = offset + 12 + content_length;
+    let end = content_end + children_length;
+    if end > data.len() {
+        return Err(truncated());
+    }
+    Ok(Chunk {
+        id: header[..4].try_into().unwrap(),
+        content: &data[offset + 12..content_end],
+        children: &data[content_end..end],
+        end,
+    })
+}
+
+fn fit_model(model: Model, fit: Fit) -> Result<Model, PlanetDataError> {
+    if model.size.iter().all(|size| *size <= GRID_SIZE) {
+        return Ok(model);
+    }
+
+    // Trim the empty space around the voxels first
+    let mut min = [usize::MAX; 3];
+    let mut max = [0; 3];
+    for (position, _) in &model.voxels {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(position[axis]);
+            max[axis] = max[axis].max(position[axis]);
+        }
+    }
+    let size = [0, 1, 2].map(|axis| max[axis] - min[axis] + 1);
+    let voxels = model
+        .voxels
+        .into_iter()
+        .map(|(position, color)| ([0, 1, 2].map(|axis| position[axis] - min[axis]), color));
+    if size.iter().all(|size| *size <= GRID_SIZE) {
+        return Ok(Model {
+            size,
+            voxels: voxels.collect(),
+        });
+    }
+
+    match fit {
+        Fit::Reject => Err(PlanetDataError::ModelTooLarge {
+            size: format!("{}x{}x{}", size[0], size[1], size[2]),
+            max: GRID_SIZE,
+        }),
+        Fit::Crop => {
+            let start = size.map(|size| size.saturating_sub(GRID_SIZE) / 2);
+            let voxels = voxels
+                .filter(|(position, _)| {
+                    (0..3).all(|axis| {
+                        position[axis] >= start[axis] && position[axis] < start[axis] + GRID_SIZE
+                    })
+                })
+                .map(|(position, color)| ([0, 1, 2].map(|axis| position[axis] - start[axis]), color))
+                .collect();
+            Ok(Model {
+                size: size.map(|size| size.min(GRID_SIZE)),
+                voxels,
+            })
+        }
+        Fit::Scale => {
+            let largest = *size.iter().max().unwrap();
+            let scaled = |value: usize| value * GRID_SIZE / largest;
+            // Every block takes the most common color of the voxels falling into it
+            let mut cells: HashMap<[usize; 3], HashMap<u8, usize>> = HashMap::new();
+            for (position, color) in voxels {
+                *cells
+                    .entry(position.map(scaled))
+                    .or_default()
+                    .entry(color)
+                    .or_default() += 1;
+            }
+            let mut voxels: Vec<([usize; 3], u8)> = cells
+                .into_iter()
+                .map(|(position, colors)| {
+                    let color = colors
+                        .into_iter()
+                        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+                        .unwrap()
+                        .0;
+                    (position, color)
+                })
+                .collect();
+            voxels.sort_unstable();
+            Ok(Model {
+                size: size.map(|size| (size * GRID_SIZE).div_ceil(largest)),
+                voxels,
+            })
+        }
+    }
+}
+
+/// Reduces weighted colors to at most `size` colors by merging the closest pair
+/// until few enough remain, the most used colors come first
+fn quantize(colors: &[(Color, usize)], size: usize) -> Vec<Color> {
+    let mut clusters: Vec<([f64; 3], usize)> = colors
+        .iter()
+        .map(|(color, count)| ([color.r, color.g, color.b].map(f64::from), *count))
+        .collect();
+    while clusters.len() > size {
+        let mut closest = (0, 1, f64::MAX);
+        for a in 0..clusters.len() {
+            for b in a + 1..clusters.len() {
+                let distance = (0..3)
+                    .map(|channel| (clusters[a].0[channel] - clusters[b].0[channel]).powi(2))
+                    .sum::<f64>();
+                if distance < closest.2 {
+                    closest = (a, b, distance);
+                }
+            }
+        }
+        let (a, b, _) = closest;
+        let (merged, count) = clusters.remove(b);
+        let total = clusters[a].1 + count;
+        for (channel, value) in merged.iter().enumerate() {
+            clusters[a].0[channel] =
+                (clusters[a].0[channel] * clusters[a].1 as f64 + value * count as f64) / total as f64;
+        }
+        clusters[a].1 = total;
+    }
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.1));
+    clusters
+        .into_iter()
+        .map(|(color, _)| {
+            let [r, g, b] = color.map(|channel| channel.round() as u8);
+            Color::new(r, g, b)
+        })
+        .collect()
+}
+
+/// Index of the palette color closest to `color`
+fn nearest(palette: &[Color], color: Color) -> u8 {
+    let distance = |other: &Color| {
+        [
+            i32::from(other.r) - i32::from(color.r),
+            i32::from(other.g) - i32::from(color.g),
+            i32::from(other.b) - i32::from(color.b),
+        ]
+        .iter()
+        .map(|delta| delta * delta)
+        .sum::<i32>()
+    };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, other)| distance(other))
+        .map(|(index, _)| index as u8)
+        .unwrap_or_default()
+}
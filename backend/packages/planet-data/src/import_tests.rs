@@ -0,0 +1,126 @@
+#![cfg(test)]
+use crate::export::to_magica_voxel;
+use crate::import::{from_magica_voxel, Fit};
+use crate::vox::{EMPTY_BLOCK, GRID_SIZE, VOX_PALETTE_SIZE};
+use crate::{Color, PlanetDataError, VoxPlanet};
+
+const VOX_DEFAULT: &str = include_str!("../fixtures/vox-default.txt");
+
+/// Builds a .vox file with a single model and a palette of `colors`
+fn magica_voxel(size: [u32; 3], voxels: &[[u8; 4]], colors: &[Color]) -> Vec<u8> {
+    let mut children = vec![];
+    let mut chunk = |id: &[u8], content: Vec<u8>| {
+        children.extend_from_slice(id);
+        children.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        children.extend_from_slice(&0u32.to_le_bytes());
+        children.extend_from_slice(&content);
+    };
+    chunk(b"SIZE", size.iter().flat_map(|size| size.to_le_bytes()).collect());
+    let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+    xyzi.extend(voxels.iter().flatten());
+    chunk(b"XYZI", xyzi);
+    let mut rgba = vec![0; 1024];
+    for (index, color) in colors.iter().enumerate() {
+        rgba[index * 4..index * 4 + 4].copy_from_slice(&[color.r, color.g, color.b, 255]);
+    }
+    chunk(b"RGBA", rgba);
+
+    let mut file = b"VOX \x96\0\0\0MAIN\0\0\0\0".to_vec();
+    file.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    file.extend_from_slice(&children);
+    file
+}
+
+fn filled(planet: &VoxPlanet) -> usize {
+    planet.blocks.iter().filter(|block| **block != EMPTY_BLOCK).count()
+}
+
+#[test]
+fn exported_planets_import_unchanged() {
+    let planet = VoxPlanet::decode(VOX_DEFAULT.trim()).unwrap();
+    let imported = from_magica_voxel(&to_magica_voxel(&planet), Fit::Reject).unwrap();
+    imported.validate().unwrap();
+    for (original, block) in planet.blocks.iter().zip(imported.blocks.iter()) {
+        if *original == EMPTY_BLOCK {
+            assert_eq!(*block, EMPTY_BLOCK);
+        } else {
+            assert_eq!(imported.palette[*block as usize], planet.palette[*original as usize]);
+        }
+    }
+    assert!(imported.encode().unwrap().starts_with("VOX1="));
+}
+
+#[test]
+fn small_models_are_centered() {
+    let red = Color::new(255, 0, 0);
+    let file = magica_voxel([2, 4, 6], &[[0, 0, 0, 1], [1, 3, 5, 1]], &[red]);
+    let planet = from_magica_voxel(&file, Fit::Reject).unwrap();
+    assert_eq!(filled(&planet), 2);
+    // Model (x, y, z) with z up lands on planet (7 + x, 5 + z, 6 + 3 - y)
+    assert_eq!(planet.block(7, 5, 9), 0);
+    assert_eq!(planet.block(8, 10, 6), 0);
+    assert_eq!(planet.palette[0], red);
+    assert_eq!(planet.palette.len(), VOX_PALETTE_SIZE);
+}
+
+#[test]
+fn palette_is_quantized_to_planet_colors() {
+    let colors: Vec<Color> = (0..40).map(|i| Color::new(i * 6, 255 - i * 6, 100)).collect();
+    let voxels: Vec<[u8; 4]> = (0..40).map(|i| [i % 16, i / 16, 0, i + 1]).collect();
+    let planet = from_magica_voxel(&magica_voxel([16, 3, 1], &voxels, &colors), Fit::Reject).unwrap();
+    planet.validate().unwrap();
+    assert_eq!(filled(&planet), 40);
+    let used: std::collections::HashSet<u8> = planet.blocks.iter().copied().collect();
+    assert_eq!(used.len(), VOX_PALETTE_SIZE + 1);
+    // Neighbouring colors end up on close palette entries
+    let first = planet.palette[planet.block(0, 7, 8) as usize];
+    assert!(i32::from(first.r) < 30 && i32::from(first.g) > 225);
+}
+
+#[test]
+fn large_models_are_trimmed_cropped_or_scaled() {
+    let blue = Color::new(0, 0, 255);
+    // Lots of empty space around the voxels is trimmed without complaint
+    let file = magica_voxel([40, 40, 40], &[[10, 10, 10, 1], [25, 25, 25, 1]], &[blue]);
+    assert_eq!(filled(&from_magica_voxel(&file, Fit::Reject).unwrap()), 2);
+
+    let row: Vec<[u8; 4]> = (0..32).map(|x| [x, 0, 0, 1]).collect();
+    let file = magica_voxel([32, 1, 1], &row, &[blue]);
+    assert_eq!(
+        from_magica_voxel(&file, Fit::Reject),
+        Err(PlanetDataError::ModelTooLarge {
+            size: "32x1x1".to_string(),
+            max: GRID_SIZE,
+        })
+    );
+    let cropped = from_magica_voxel(&file, Fit::Crop).unwrap();
+    assert_eq!(filled(&cropped), GRID_SIZE);
+    let scaled = from_magica_voxel(&file, Fit::Scale).unwrap();
+    assert_eq!(filled(&scaled), GRID_SIZE);
+    assert_eq!(scaled.block(15, 7, 7), 0);
+}
+
+#[test]
+fn broken_files_are_rejected() {
+    let blue = [Color::new(0, 0, 255)];
+    let err = |file: &[u8]| from_magica_voxel(file, Fit::Scale).unwrap_err().to_string();
+    assert_eq!(err(b"PNG"), "Invalid MagicaVoxel model: not a MagicaVoxel file");
+    assert_eq!(err(&magica_voxel([4, 4, 4], &[], &blue)), "The model has no voxels");
+    assert_eq!(
+        err(&magica_voxel([4, 4, 4], &[[4, 0, 0, 1]], &blue)),
+        "Invalid MagicaVoxel model: a voxel lies outside the model size"
+    );
+    let file = magica_voxel([4, 4, 4], &[[0, 0, 0, 1]], &blue);
+    assert_eq!(err(&file[..file.len() - 8]), "Invalid MagicaVoxel model: a chunk is truncated");
+
+    // A second SIZE and XYZI pair after the palette
+    let mut file = magica_voxel([4, 4, 4], &[[0, 0, 0, 1]], &blue);
+    let second_model = file[20..64].to_vec();
+    file.extend_from_slice(&second_model);
+    let length = (file.len() - 20) as u32;
+    file[16..20].copy_from_slice(&length.to_le_bytes());
+    assert_eq!(
+        err(&file),
+        "The file holds 2 models, only single model files can be imported"
+    );
+}
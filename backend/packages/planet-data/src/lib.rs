@@ -0,0 +1,86 @@
+mod bits;
+mod codec_tests;
+mod color;
+pub mod diff;
+mod error;
+pub mod export;
+mod export_tests;
+pub mod geo;
+pub mod import;
+mod import_tests;
+pub mod mesh;
+mod png;
+pub mod render;
+mod render_tests;
+mod scenery;
+pub mod vox;
+
+pub use crate::color::Color;
+pub use crate::diff::{diff, Change};
+pub use crate::error::PlanetDataError;
+pub use crate::geo::{GeoPlanet, Layer, Vertex};
+pub use crate::mesh::{Icosphere, Mesh};
+pub use crate::png::Image;
+pub use crate::render::{render, thumbnail};
+pub use crate::scenery::{Scenery, SceneryItem};
+pub use crate::vox::VoxPlanet;
+
+use serde::{Deserialize, Serialize};
+
+/// Decoded `data` of a planet token
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "format")]
+pub enum PlanetData {
+    #[serde(rename = "GEO1")]
+    Geo(GeoPlanet),
+    #[serde(rename = "VOX1")]
+    Vox(VoxPlanet),
+}
+
+impl PlanetData {
+    pub fn decode(data: &str) -> Result<Self, PlanetDataError> {
+        let format = data.split('=').next().unwrap_or_default();
+        match format {
+            geo::GEO_FORMAT => GeoPlanet::decode(data).map(PlanetData::Geo),
+            vox::VOX_FORMAT => VoxPlanet::decode(data).map(PlanetData::Vox),
+            _ => Err(PlanetDataError::UnknownFormat {
+                format: format.to_string(),
+            }),
+        }
+    }
+
+    pub fn encode(&self) -> Result<String, PlanetDataError> {
+        match self {
+            PlanetData::Geo(planet) => planet.encode(),
+            PlanetData::Vox(planet) => planet.encode(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), PlanetDataError> {
+        match self {
+            PlanetData::Geo(planet) => planet.validate(),
+            PlanetData::Vox(planet) => planet.validate(),
+        }
+    }
+}
+
+/// Splits a data string on '=' and checks the format prefix and the number of sections
+pub(crate) fn split_sections<'a>(
+    data: &'a str,
+    format: &str,
+    count: usize,
+) -> Result<Vec<&'a str>, PlanetDataError> {
+    let sections: Vec<&str> = data.split('=').collect();
+    if sections[0] != format {
+        return Err(PlanetDataError::UnknownFormat {
+            format: sections[0].to_string(),
+        });
+    }
+    if sections.len() != count {
+        return Err(PlanetDataError::SectionCount {
+            expected: count,
+            found: sections.len(),
+        });
+    }
+    Ok(sections)
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use crate::geo::{GeoPlanet, Layer, HEIGHT_PRECISION};
+use crate::vox::{VoxPlanet, EMPTY_BLOCK, GRID_SIZE};
+use crate::Color;
+
+/// Terrain heights of GEO1 planets run from `MIN_HEIGHT` to `MAX_HEIGHT` planet radii
+pub const MIN_HEIGHT: f64 = 1.0;
+pub const MAX_HEIGHT: f64 = 3.0;
+/// Width of the whole VOX1 grid in the same units
+pub const VOX_WIDTH: f64 = 4.0;
+
+const ICOSPHERE_ITERATIONS: u32 = 3;
+
+/// Triangle mesh with one color per vertex, triangles wind counter clockwise seen from outside
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub positions: Vec<[f64; 3]>,
+    pub colors: Vec<Color>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+/// Subdivided icosahedron the GEO1 terrain is built on, with the vertex and
+/// face order of the frontend so vertex `i` is `GeoPlanet::terrain[i]`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Icosphere {
+    pub vertices: Vec<[f64; 3]>,
+    pub faces: Vec<[usize; 3]>,
+}
+
+const INITIAL_VERTICES: [[f64; 3]; 12] = [
+    [0.0, 1.0, 0.0],
+    [0.276385, 0.447215, -0.85064],
+    [0.894425, 0.447215, 0.0],
+    [0.276385, 0.447215, 0.85064],
+    [-0.7236, 0.447215, 0.52572],
+    [-0.7236, 0.447215, -0.52572],
+    [-0.276385, -0.447215, -0.85064],
+    [0.7236, -0.447215, -0.52572],
+    [0.7236, -0.447215, 0.52572],
+    [-0.276385, -0.447215, 0.85064],
+    [-0.894425, -0.447215, 0.0],
+    [0.0, -1.0, 0.0],
+];
+
+const INITIAL_TRIANGLES: [[usize; 3]; 20] = [
+    [2, 1, 0],
+    [3, 2, 0],
+    [4, 3, 0],
+    [5, 4, 0],
+    [1, 5, 0],
+    [5, 1, 6],
+    [7, 6, 1],
+    [1, 2, 7],
+    [8, 7, 2],
+    [2, 3, 8],
+    [9, 8, 3],
+    [3, 4, 9],
+    [10, 9, 4],
+    [4, 5, 10],
+    [6, 10, 5],
+    [6, 7, 11],
+    [7, 8, 11],
+    [8, 9, 11],
+    [9, 10, 11],
+    [10, 6, 11],
+];
+
+impl Icosphere {
+    pub fn new() -> Self {
+        let mut sphere = Icosphere {
+            vertices: vec![],
+            faces: vec![],
+        };
+        let mut cache = HashMap::new();
+        for triangle in INITIAL_TRIANGLES.iter() {
+            let points = [
+                normalize(INITIAL_VERTICES[triangle[0]]),
+                normalize(INITIAL_VERTICES[triangle[1]]),
+                normalize(INITIAL_VERTICES[triangle[2]]),
+            ];
+            sphere.iterate_face(&mut cache, points, ICOSPHERE_ITERATIONS);
+        }
+        sphere
+    }
+
+    fn iterate_face(
+        &mut self,
+        cache: &mut HashMap<String, usize>,
+        [a, b, c]: [[f64; 3]; 3],
+        iterations: u32,
+    ) {
+        if iterations == 0 {
+            let face = [
+                self.cached_vertex(cache, a),
+                self.cached_vertex(cache, b),
+                self.cached_vertex(cache, c),
+            ];
+            self.faces.push(face);
+        } else {
+            let ab = normalize(add(a, b));
+            let bc = normalize(add(b, c));
+            let ca = normalize(add(c, a));
+            self.iterate_face(cache, [a, ab, ca], iterations - 1);
+            self.iterate_face(cache, [ab, b, bc], iterations - 1);
+            self.iterate_face(cache, [bc, ca, ab], iterations - 1);
+            self.iterate_face(cache, [ca, bc, c], iterations - 1);
+        }
+    }
+
+    /// Shared vertices are matched on coordinates rounded to two decimals, like the frontend does
+    fn cached_vertex(&mut self, cache: &mut HashMap<String, usize>, point: [f64; 3]) -> usize {
+        let key = format!("{:.2}-{:.2}-{:.2}", point[0], point[1], point[2]);
+        let vertices = &mut self.vertices;
+        *cache.entry(key).or_insert_with(|| {
+            vertices.push(point);
+            vertices.len() - 1
+        })
+    }
+}
+
+impl Default for Icosphere {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Distance of a terrain vertex from the planet center
+pub fn terrain_height(height: u8) -> f64 {
+    MIN_HEIGHT + f64::from(height) / f64::from(HEIGHT_PRECISION) * (MAX_HEIGHT - MIN_HEIGHT)
+}
+
+impl Layer {
+    /// Radius of the water sphere, `None` when there is no water
+    pub fn water_radius(&self) -> Option<f64> {
+        if self.height == 0 {
+            return None;
+        }
+        let range = MAX_HEIGHT - MIN_HEIGHT;
+        Some(MIN_HEIGHT + f64::from(self.height) / 255.0 * range - range / 510.0)
+    }
+
+    /// Outer radius of the atmosphere glow
+    pub fn atmosphere_radius(&self) -> f64 {
+        1.0 + f64::from(self.height) / 255.0 * 4.0
+    }
+}
+
+impl GeoPlanet {
+    /// Terrain with a vertex per terrain entry, colored by the palette
+    pub fn terrain_mesh(&self) -> Mesh {
+        let sphere = Icosphere::new();
+        let positions = sphere
+            .vertices
+            .iter()
+            .zip(self.terrain.iter())
+            .map(|(normal, vertex)| scale(*normal, terrain_height(vertex.height)))
+            .collect();
+        let colors = self
+            .terrain
+            .iter()
+            .map(|vertex| self.palette[vertex.color as usize])
+            .collect();
+        Mesh {
+            positions,
+            colors,
+            triangles: sphere.faces,
+        }
+    }
+}
+
+/// Block faces as (outward normal, corners counter clockwise seen from outside)
+const BLOCK_FACES: [([i32; 3], [[i32; 3]; 4]); 6] = [
+    ([0, 0, 1], [[-1, -1, 1], [1, -1, 1], [1, 1, 1], [-1, 1, 1]]),
+    ([1, 0, 0], [[1, -1, 1], [1, -1, -1], [1, 1, -1], [1, 1, 1]]),
+    (
+        [0, 0, -1],
+        [[1, -1, -1], [-1, -1, -1], [-1, 1, -1], [1, 1, -1]],
+    ),
+    (
+        [-1, 0, 0],
+        [[-1, -1, -1], [-1, -1, 1], [-1, 1, 1], [-1, 1, -1]],
+    ),
+    ([0, 1, 0], [[-1, 1, 1], [1, 1, 1], [1, 1, -1], [-1, 1, -1]]),
+    (
+        [0, -1, 0],
+        [[-1, -1, -1], [1, -1, -1], [1, -1, 1], [-1, -1, 1]],
+    ),
+];
+
+impl VoxPlanet {
+    /// Center of block (x, y, z), the grid being centered on the origin
+    pub fn block_position(x: usize, y: usize, z: usize) -> [f64; 3] {
+        let size = VOX_WIDTH / GRID_SIZE as f64;
+        let offset = -(GRID_SIZE as f64) / 2.0 + 0.5;
+        [
+            (x as f64 + offset) * size,
+            (y as f64 + offset) * size,
+            (z as f64 + offset) * size,
+        ]
+    }
+
+    /// Block faces that are not covered by a neighbouring block
+    pub fn terrain_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::default();
+        let half = VOX_WIDTH / GRID_SIZE as f64 / 2.0;
+        for x in 0..GRID_SIZE {
+            for y in 0..GRID_SIZE {
+                for z in 0..GRID_SIZE {
+                    let block = self.block(x, y, z);
+                    if block == EMPTY_BLOCK {
+                        continue;
+                    }
+                    let center = Self::block_position(x, y, z);
+                    for (normal, corners) in BLOCK_FACES.iter() {
+                        if !self.is_open(x, y, z, *normal) {
+                            continue;
+                        }
+                        let first = mesh.positions.len();
+                        for corner in corners.iter() {
+                            let corner = corner.map(f64::from);
+                            mesh.positions.push(add(center, scale(corner, half)));
+                            mesh.colors.push(self.palette[block as usize]);
+                        }
+                        mesh.triangles.push([first, first + 1, first + 2]);
+                        mesh.triangles.push([first, first + 2, first + 3]);
+                    }
+                }
+            }
+        }
+        mesh
+    }
+
+    fn is_open(&self, x: usize, y: usize, z: usize, [dx, dy, dz]: [i32; 3]) -> bool {
+        let neighbour = |value: usize, delta: i32| {
+            let value = value as i32 + delta;
+            if value < 0 || value >= GRID_SIZE as i32 {
+                None
+            } else {
+                Some(value as usize)
+            }
+        };
+        match (neighbour(x, dx), neighbour(y, dy), neighbour(z, dz)) {
+            (Some(x), Some(y), Some(z)) => self.block(x, y, z) == EMPTY_BLOCK,
+            _ => true,
+        }
+    }
+}
+
+pub(crate) fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a: [f64; 3], factor: f64) -> [f64; 3] {
+    [a[0] * factor, a[1] * factor, a[2] * factor]
+}
+
+pub(crate) fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn normalize(a: [f64; 3]) -> [f64; 3] {
+    let length = dot(a, a).sqrt();
+    if length == 0.0 {
+        a
+    } else {
+        scale(a, 1.0 / length)
+    }
+}
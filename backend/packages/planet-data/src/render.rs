@@ -0,0 +1,289 @@
+use crate::geo::{GeoPlanet, Layer};
+use crate::mesh::{cross, dot, normalize, sub, Mesh};
+use crate::png::Image;
+use crate::{Color, PlanetData, PlanetDataError};
+
+pub const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+
+/// Samples per pixel along each axis
+const SUPERSAMPLING: u32 = 2;
+/// Part of the image width left around the planet
+const MARGIN: f64 = 0.08;
+const AMBIENT_LIGHT: f64 = 0.35;
+const CAMERA_YAW: f64 = std::f64::consts::FRAC_PI_4;
+const CAMERA_PITCH: f64 = std::f64::consts::FRAC_PI_6;
+
+/// Renders the `data` string of a planet into a square PNG thumbnail
+pub fn thumbnail(data: &str, size: u32) -> Result<Vec<u8>, PlanetDataError> {
+    Ok(render(&PlanetData::decode(data)?, size).to_png())
+}
+
+/// Draws the terrain seen from above at an isometric angle on a transparent
+/// background. GEO1 planets get their water and atmosphere, scenery models
+/// live in the frontend and are left out
+pub fn render(planet: &PlanetData, size: u32) -> Image {
+    match planet {
+        PlanetData::Geo(planet) => render_geo(planet, size),
+        PlanetData::Vox(planet) => {
+            let mesh = planet.terrain_mesh();
+            let mut canvas = Canvas::new(size, mesh_radius(&mesh));
+            canvas.draw_mesh(&mesh);
+            canvas.resolve()
+        }
+    }
+}
+
+fn render_geo(planet: &GeoPlanet, size: u32) -> Image {
+    let mesh = planet.terrain_mesh();
+    let water = planet.water.water_radius();
+    let radius = mesh_radius(&mesh).max(water.unwrap_or_default());
+    let mut canvas = Canvas::new(size, radius);
+    canvas.draw_mesh(&mesh);
+    if let Some(water_radius) = water {
+        canvas.draw_water(&planet.water, water_radius);
+    }
+    if planet.atmosphere.density > 0 {
+        canvas.draw_atmosphere(&planet.atmosphere);
+    }
+    canvas.resolve()
+}
+
+fn mesh_radius(mesh: &Mesh) -> f64 {
+    mesh.positions
+        .iter()
+        .map(|position| dot(*position, *position).sqrt())
+        .fold(0.0, f64::max)
+}
+
+/// Orthographic camera looking at the origin
+struct Camera {
+    right: [f64; 3],
+    up: [f64; 3],
+    /// Points from the origin towards the camera
+    forward: [f64; 3],
+    light: [f64; 3],
+}
+
+impl Camera {
+    fn new() -> Self {
+        let forward = [
+            CAMERA_YAW.sin() * CAMERA_PITCH.cos(),
+            CAMERA_PITCH.sin(),
+            CAMERA_YAW.cos() * CAMERA_PITCH.cos(),
+        ];
+        let right = normalize(cross([0.0, 1.0, 0.0], forward));
+        let up = cross(forward, right);
+        let light = normalize([
+            right[0] * -0.4 + up[0] * 0.6 + forward[0] * 0.7,
+            right[1] * -0.4 + up[1] * 0.6 + forward[1] * 0.7,
+            right[2] * -0.4 + up[2] * 0.6 + forward[2] * 0.7,
+        ]);
+        Camera {
+            right,
+            up,
+            forward,
+            light,
+        }
+    }
+
+    fn shade(&self, normal: [f64; 3]) -> f64 {
+        AMBIENT_LIGHT + (1.0 - AMBIENT_LIGHT) * dot(normal, self.light).max(0.0)
+    }
+}
+
+/// Supersampled color and depth buffers, colors are linear 0..1 with straight alpha
+struct Canvas {
+    camera: Camera,
+    size: u32,
+    scale: f64,
+    colors: Vec<[f64; 3]>,
+    alpha: Vec<f64>,
+    depth: Vec<f64>,
+}
+
+impl Canvas {
+    fn new(size: u32, radius: f64) -> Self {
+        let samples = size * SUPERSAMPLING;
+        let count = (samples * samples) as usize;
+        Canvas {
+            camera: Camera::new(),
+            size: samples,
+            scale: f64::from(samples) / 2.0 * (1.0 - MARGIN) / radius.max(f64::EPSILON),
+            colors: vec![[0.0; 3]; count],
+            alpha: vec![0.0; count],
+            depth: vec![f64::NEG_INFINITY; count],
+        }
+    }
+
+    /// Sample coordinates and depth of a point, larger depths are closer to the camera
+    fn project(&self, point: [f64; 3]) -> [f64; 3] {
+        let half = f64::from(self.size) / 2.0;
+        [
+            half + dot(point, self.camera.right) * self.scale,
+            half - dot(point, self.camera.up) * self.scale,
+            dot(point, self.camera.forward),
+        ]
+    }
+
+    /// View plane coordinates of the center of a sample
+    fn unproject(&self, x: u32, y: u32) -> (f64, f64) {
+        let half = f64::from(self.size) / 2.0;
+        (
+            (f64::from(x) + 0.5 - half) / self.scale,
+            (half - f64::from(y) - 0.5) / self.scale,
+        )
+    }
+
+    fn draw_mesh(&mut self, mesh: &Mesh) {
+        for triangle in &mesh.triangles {
+            let [a, b, c] = triangle.map(|index| mesh.positions[index]);
+            let normal = normalize(cross(sub(b, a), sub(c, a)));
+            if dot(normal, self.camera.forward) <= 0.0 {
+                continue;
+            }
+            let light = self.camera.shade(normal);
+            let colors = triangle.map(|index| lit(mesh.colors[index], light));
+            self.fill_triangle([self.project(a), self.project(b), self.project(c)], colors);
+        }
+    }
+
+    fn fill_triangle(&mut self, points: [[f64; 3]; 3], colors: [[f64; 3]; 3]) {
+        let [a, b, c] = points;
+        let area = edge(a, b, c);
+        if area.abs() < f64::EPSILON {
+            return;
+        }
+        let min_x = a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32;
+        let min_y = a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32;
+        let max_x = (a[0].max(b[0]).max(c[0]).ceil() as u32).min(self.size);
+        let max_y = (a[1].max(b[1]).max(c[1]).ceil() as u32).min(self.size);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let sample = [f64::from(x) + 0.5, f64::from(y) + 0.5, 0.0];
+                let weights = [
+                    edge(b, c, sample) / area,
+                    edge(c, a, sample) / area,
+                    edge(a, b, sample) / area,
+                ];
+                if weights.iter().any(|weight| *weight < 0.0) {
+                    continue;
+                }
+                let depth = weights[0] * a[2] + weights[1] * b[2] + weights[2] * c[2];
+                let index = (y * self.size + x) as usize;
+                if depth <= self.depth[index] {
+                    continue;
+                }
+                self.depth[index] = depth;
+                self.alpha[index] = 1.0;
+                self.colors[index] = [0, 1, 2].map(|channel| {
+                    weights[0] * colors[0][channel]
+                        + weights[1] * colors[1][channel]
+                        + weights[2] * colors[2][channel]
+                });
+            }
+        }
+    }
+
+    /// Raycasts the water sphere, blending it over the terrain in front of it
+    fn draw_water(&mut self, water: &Layer, radius: f64) {
+        let opacity = 0.35 + 0.6 * f64::from(water.density) / 255.0;
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let (u, v) = self.unproject(x, y);
+                let remaining = radius * radius - u * u - v * v;
+                if remaining < 0.0 {
+                    continue;
+                }
+                let depth = remaining.sqrt();
+                let index = (y * self.size + x) as usize;
+                if depth <= self.depth[index] {
+                    continue;
+                }
+                let normal = [0, 1, 2].map(|axis| {
+                    (self.camera.right[axis] * u
+                        + self.camera.up[axis] * v
+                        + self.camera.forward[axis] * depth)
+                        / radius
+                });
+                let color = lit(water.color, self.camera.shade(normal));
+                self.blend(index, color, opacity);
+                self.depth[index] = depth;
+            }
+        }
+    }
+
+    /// Glow that fades out towards the edge of the atmosphere
+    fn draw_atmosphere(&mut self, atmosphere: &Layer) {
+        let radius = atmosphere.atmosphere_radius();
+        let density = f64::from(atmosphere.density) / 255.0;
+        let color = lit(atmosphere.color, 1.0);
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let (u, v) = self.unproject(x, y);
+                let distance = (u * u + v * v).sqrt() / radius;
+                if distance >= 1.0 {
+                    continue;
+                }
+                let index = (y * self.size + x) as usize;
+                self.blend(
+                    index,
+                    color,
+                    0.5 * density * (1.0 - distance * distance).sqrt(),
+                );
+            }
+        }
+    }
+
+    fn blend(&mut self, index: usize, color: [f64; 3], opacity: f64) {
+        let below = self.alpha[index] * (1.0 - opacity);
+        let alpha = opacity + below;
+        if alpha > 0.0 {
+            let old = self.colors[index];
+            self.colors[index] =
+                [0, 1, 2].map(|channel| (color[channel] * opacity + old[channel] * below) / alpha);
+        }
+        self.alpha[index] = alpha;
+    }
+
+    /// Averages the samples of every pixel into the final image
+    fn resolve(&self) -> Image {
+        let size = self.size / SUPERSAMPLING;
+        let mut image = Image::new(size, size);
+        let samples = f64::from(SUPERSAMPLING * SUPERSAMPLING);
+        for y in 0..size {
+            for x in 0..size {
+                let mut color = [0.0; 3];
+                let mut alpha = 0.0;
+                for sy in 0..SUPERSAMPLING {
+                    for sx in 0..SUPERSAMPLING {
+                        let index = ((y * SUPERSAMPLING + sy) * self.size + x * SUPERSAMPLING + sx)
+                            as usize;
+                        for (channel, value) in color.iter_mut().enumerate() {
+                            *value += self.colors[index][channel] * self.alpha[index];
+                        }
+                        alpha += self.alpha[index];
+                    }
+                }
+                if alpha == 0.0 {
+                    continue;
+                }
+                let [r, g, b] = color.map(|value| to_byte(value / alpha));
+                image.set_pixel(x, y, [r, g, b, to_byte(alpha / samples)]);
+            }
+        }
+        image
+    }
+}
+
+/// Twice the signed area of the triangle (a, b, p)
+fn edge(a: [f64; 3], b: [f64; 3], p: [f64; 3]) -> f64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+fn lit(color: Color, light: f64) -> [f64; 3] {
+    [color.r, color.g, color.b].map(|channel| f64::from(channel) / 255.0 * light)
+}
+
+fn to_byte(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
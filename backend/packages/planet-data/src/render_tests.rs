@@ -0,0 +1,111 @@
+#![cfg(test)]
+use crate::geo::{UNIQUE_FACES, UNIQUE_VERTICES};
+use crate::mesh::{cross, dot, sub};
+use crate::png::crc32;
+use crate::vox::{BLOCK_COUNT, EMPTY_BLOCK};
+use crate::{render, thumbnail, Icosphere, Image, PlanetData, VoxPlanet};
+
+const GEO_DEFAULT: &str = include_str!("../fixtures/geo-default.txt");
+const VOX_DEFAULT: &str = include_str!("../fixtures/vox-default.txt");
+
+fn empty_vox_planet() -> VoxPlanet {
+    let mut planet = VoxPlanet::decode(VOX_DEFAULT.trim()).unwrap();
+    planet.blocks = vec![EMPTY_BLOCK; BLOCK_COUNT];
+    planet
+}
+
+#[test]
+fn icosphere_matches_terrain_layout() {
+    let sphere = Icosphere::new();
+    assert_eq!(sphere.vertices.len(), UNIQUE_VERTICES);
+    assert_eq!(sphere.faces.len(), UNIQUE_FACES);
+    for vertex in &sphere.vertices {
+        assert!((dot(*vertex, *vertex) - 1.0).abs() < 1e-9);
+    }
+    // The frontend numbers the vertices as it walks the faces of the icosahedron
+    assert_eq!(sphere.faces[0], [0, 1, 2]);
+    for [a, b, c] in sphere
+        .faces
+        .iter()
+        .map(|face| face.map(|i| sphere.vertices[i]))
+    {
+        let normal = cross(sub(b, a), sub(c, a));
+        assert!(dot(normal, a) > 0.0, "faces wind outwards");
+    }
+}
+
+#[test]
+fn vox_mesh_skips_covered_faces() {
+    let mut planet = empty_vox_planet();
+    assert!(planet.terrain_mesh().triangles.is_empty());
+
+    planet.set_block(3, 4, 5, 2);
+    let mesh = planet.terrain_mesh();
+    assert_eq!(mesh.triangles.len(), 12);
+    assert_eq!(mesh.positions.len(), 24);
+    assert!(mesh.colors.iter().all(|color| *color == planet.palette[2]));
+    for [a, b, c] in mesh.triangles.iter().map(|t| t.map(|i| mesh.positions[i])) {
+        let center = VoxPlanet::block_position(3, 4, 5);
+        let normal = cross(sub(b, a), sub(c, a));
+        assert!(dot(normal, sub(a, center)) > 0.0, "faces wind outwards");
+    }
+
+    planet.set_block(3, 5, 5, 7);
+    assert_eq!(planet.terrain_mesh().triangles.len(), 20);
+}
+
+#[test]
+fn png_chunks_are_well_formed() {
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+
+    let mut image = Image::new(3, 2);
+    image.set_pixel(2, 1, [1, 2, 3, 4]);
+    let png = image.to_png();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+    let mut chunks = vec![];
+    let mut offset = 8;
+    while offset < png.len() {
+        let length = u32::from_be_bytes([
+            png[offset],
+            png[offset + 1],
+            png[offset + 2],
+            png[offset + 3],
+        ]) as usize;
+        let body = &png[offset + 4..offset + 8 + length];
+        let crc = &png[offset + 8 + length..offset + 12 + length];
+        assert_eq!(crc, crc32(body).to_be_bytes());
+        chunks.push((
+            String::from_utf8(body[..4].to_vec()).unwrap(),
+            body[4..].to_vec(),
+        ));
+        offset += 12 + length;
+    }
+    let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+    assert_eq!(kinds, vec!["IHDR", "IDAT", "IEND"]);
+    assert_eq!(chunks[0].1, vec![0, 0, 0, 3, 0, 0, 0, 2, 8, 6, 0, 0, 0]);
+
+    // A single stored block holding both filtered rows
+    let idat = &chunks[1].1;
+    assert_eq!(&idat[..3], &[0x78, 0x01, 1]);
+    assert_eq!(&idat[3..5], &26u16.to_le_bytes());
+    assert_eq!(&idat[7 + 14..7 + 26], &[0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn render_draws_planet_on_transparent_background() {
+    for data in [GEO_DEFAULT, VOX_DEFAULT].iter() {
+        let image = render(&PlanetData::decode(data.trim()).unwrap(), 64);
+        assert_eq!((image.width, image.height), (64, 64));
+        // Only a thin atmosphere reaches the corners
+        assert!(image.pixel(0, 0)[3] < 32);
+        assert_eq!(image.pixel(32, 32)[3], 255);
+    }
+
+    let empty = render(&PlanetData::Vox(empty_vox_planet()), 16);
+    assert!(empty.pixels.iter().all(|pixel| pixel[3] == 0));
+
+    let png = thumbnail(GEO_DEFAULT.trim(), 32).unwrap();
+    assert_eq!(&png[16..24], &[0, 0, 0, 32, 0, 0, 0, 32]);
+    assert!(thumbnail("VOX1=", 32).is_err());
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::error::PlanetDataError;
+
+const MODEL_PACK_BITS: u32 = 6;
+const OBJECT_ID_BITS: u32 = 6;
+
+/// Objects placed on the planet surface
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scenery {
+    /// Set of models the object ids refer to, only pack 0 exists so far
+    pub model_pack: u8,
+    pub items: Vec<SceneryItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneryItem {
+    pub object_id: u8,
+    /// Where the object stands, see the planet format for the meaning
+    pub location_id: u16,
+    /// Palette index of the object color
+    pub color_id: u8,
+}
+
+/// Bit widths and limits of the scenery section of one planet format
+pub(crate) struct SceneryLayout {
+    pub location_bits: u32,
+    pub color_bits: u32,
+    pub locations: u32,
+    pub colors: u32,
+    pub max_items: usize,
+}
+
+impl SceneryLayout {
+    fn item_bits(&self) -> u32 {
+        OBJECT_ID_BITS + self.location_bits + self.color_bits
+    }
+}
+
+impl Scenery {
+    pub(crate) fn validate(&self, layout: &SceneryLayout) -> Result<(), PlanetDataError> {
+        check_range("Model pack", 0, self.model_pack as u32, (1 << MODEL_PACK_BITS) - 1)?;
+        if self.items.len() > layout.max_items {
+            return Err(PlanetDataError::TooMuchScenery {
+                found: self.items.len(),
+                max: layout.max_items,
+            });
+        }
+        for (index, item) in self.items.iter().enumerate() {
+            check_range("Scenery object", index, item.object_id as u32, (1 << OBJECT_ID_BITS) - 1)?;
+            check_range("Scenery location", index, item.location_id as u32, layout.locations - 1)?;
+            check_range("Scenery color", index, item.color_id as u32, layout.colors - 1)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn encode(&self, layout: &SceneryLayout) -> Result<String, PlanetDataError> {
+        self.validate(layout)?;
+        let mut writer = BitWriter::default();
+        writer.write(self.model_pack as u32, MODEL_PACK_BITS);
+        for item in &self.items {
+            writer.write(item.object_id as u32, OBJECT_ID_BITS);
+            writer.write(item.location_id as u32, layout.location_bits);
+            writer.write(item.color_id as u32, layout.color_bits);
+        }
+        Ok(writer.finish())
+    }
+
+    pub(crate) fn decode(base64: &str, layout: &SceneryLayout) -> Result<Self, PlanetDataError> {
+        let mut reader = BitReader::new("scenery", base64)?;
+        if reader.remaining() < MODEL_PACK_BITS as usize {
+            return Err(PlanetDataError::EmptySection { section: "scenery" });
+        }
+        let model_pack = reader.read(MODEL_PACK_BITS) as u8;
+        let count = reader.remaining() / layout.item_bits() as usize;
+        let items = (0..count)
+            .map(|_| SceneryItem {
+                object_id: reader.read(OBJECT_ID_BITS) as u8,
+                location_id: reader.read(layout.location_bits) as u16,
+                color_id: reader.read(layout.color_bits) as u8,
+            })
+            .collect();
+        reader.finish()?;
+
+        let scenery = Scenery { model_pack, items };
+        scenery.validate(layout)?;
+        Ok(scenery)
+    }
+}
+
+pub(crate) fn check_range(
+    what: &'static str,
+    index: usize,
+    value: u32,
+    max: u32,
+) -> Result<(), PlanetDataError> {
+    if value > max {
+        return Err(PlanetDataError::OutOfRange {
+            what,
+            index,
+            value,
+            max,
+        });
+    }
+    Ok(())
+}
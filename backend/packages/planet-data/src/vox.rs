@@ -0,0 +1,160 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::color::{decode_palette, encode_palette, Color};
+use crate::error::PlanetDataError;
+use crate::geo::check_length;
+use crate::scenery::{check_range, Scenery, SceneryLayout};
+use crate::split_sections;
+
+pub const VOX_FORMAT: &str = "VOX1";
+/// Blocks along each side of the voxel grid
+pub const GRID_SIZE: usize = 16;
+pub const BLOCK_COUNT: usize = GRID_SIZE * GRID_SIZE * GRID_SIZE;
+/// Block value of an empty cell, the other values are palette indexes
+pub const EMPTY_BLOCK: u8 = 15;
+pub const VOX_PALETTE_SIZE: usize = 15;
+pub const MAX_VOX_SCENERY: usize = 256;
+
+const BLOCK_BITS: u32 = 4;
+const TERRAIN_SIZE: usize = (BLOCK_COUNT * BLOCK_BITS as usize).div_ceil(6);
+
+/// Scenery locations are `block id * 6 + face`, the block id being `y * 256 + z * 16 + x`
+const SCENERY_LAYOUT: SceneryLayout = SceneryLayout {
+    location_bits: 15,
+    color_bits: 4,
+    locations: (BLOCK_COUNT * 6) as u32,
+    colors: VOX_PALETTE_SIZE as u32,
+    max_items: MAX_VOX_SCENERY,
+};
+
+/// Voxel planet, data string "VOX1=terrain=scenery=palette"
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VoxPlanet {
+    /// Block values in data order, see `block_index`.
+    /// In JSON every horizontal slice is a list of rows, one hex digit per block and '.' when empty
+    #[serde(serialize_with = "blocks_to_slices", deserialize_with = "blocks_from_slices")]
+    pub blocks: Vec<u8>,
+    pub scenery: Scenery,
+    pub palette: Vec<Color>,
+}
+
+/// Position of block (x, y, z) in `VoxPlanet::blocks`, y points up
+pub fn block_index(x: usize, y: usize, z: usize) -> usize {
+    (x * GRID_SIZE + y) * GRID_SIZE + z
+}
+
+impl VoxPlanet {
+    pub fn block(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.blocks[block_index(x, y, z)]
+    }
+
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: u8) {
+        self.blocks[block_index(x, y, z)] = block;
+    }
+
+    pub fn validate(&self) -> Result<(), PlanetDataError> {
+        if self.blocks.len() != BLOCK_COUNT {
+            return Err(PlanetDataError::Count {
+                what: "blocks",
+                expected: BLOCK_COUNT,
+                found: self.blocks.len(),
+            });
+        }
+        for (index, block) in self.blocks.iter().enumerate() {
+            check_range("Block", index, *block as u32, EMPTY_BLOCK as u32)?;
+        }
+        if self.palette.len() != VOX_PALETTE_SIZE {
+            return Err(PlanetDataError::Count {
+                what: "palette colors",
+                expected: VOX_PALETTE_SIZE,
+                found: self.palette.len(),
+            });
+        }
+        self.scenery.validate(&SCENERY_LAYOUT)
+    }
+
+    pub fn encode(&self) -> Result<String, PlanetDataError> {
+        self.validate()?;
+        let mut terrain = BitWriter::default();
+        for block in &self.blocks {
+            terrain.write(*block as u32, BLOCK_BITS);
+        }
+        Ok([
+            VOX_FORMAT.to_string(),
+            terrain.finish(),
+            self.scenery.encode(&SCENERY_LAYOUT)?,
+            encode_palette(&self.palette, VOX_PALETTE_SIZE)?,
+        ]
+        .join("="))
+    }
+
+    pub fn decode(data: &str) -> Result<Self, PlanetDataError> {
+        let sections = split_sections(data, VOX_FORMAT, 4)?;
+
+        check_length("terrain", sections[1], TERRAIN_SIZE)?;
+        let mut reader = BitReader::new("terrain", sections[1])?;
+        let blocks = (0..BLOCK_COUNT)
+            .map(|_| reader.read(BLOCK_BITS) as u8)
+            .collect();
+        reader.finish()?;
+
+        Ok(VoxPlanet {
+            blocks,
+            scenery: Scenery::decode(sections[2], &SCENERY_LAYOUT)?,
+            palette: decode_palette(sections[3], VOX_PALETTE_SIZE)?,
+        })
+    }
+}
+
+fn blocks_to_slices<S: Serializer>(blocks: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let slices: Vec<Vec<String>> = (0..GRID_SIZE)
+        .map(|y| {
+            (0..GRID_SIZE)
+                .map(|z| {
+                    (0..GRID_SIZE)
+                        .map(|x| match blocks.get(block_index(x, y, z)) {
+                            Some(&EMPTY_BLOCK) | None => '.',
+                            Some(block) => std::char::from_digit(*block as u32, 16).unwrap_or('?'),
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+    slices.serialize(serializer)
+}
+
+fn blocks_from_slices<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    use serde::de::Error;
+
+    let slices: Vec<Vec<String>> = Vec::deserialize(deserializer)?;
+    if slices.len() != GRID_SIZE || slices.iter().any(|rows| rows.len() != GRID_SIZE) {
+        return Err(D::Error::custom(format!(
+            "blocks must be {} slices of {} rows",
+            GRID_SIZE, GRID_SIZE
+        )));
+    }
+    let mut blocks = vec![EMPTY_BLOCK; BLOCK_COUNT];
+    for (y, rows) in slices.iter().enumerate() {
+        for (z, row) in rows.iter().enumerate() {
+            if row.chars().count() != GRID_SIZE {
+                return Err(D::Error::custom(format!(
+                    "block row {:?} must be {} characters long",
+                    row, GRID_SIZE
+                )));
+            }
+            for (x, cell) in row.chars().enumerate() {
+                blocks[block_index(x, y, z)] = match cell {
+                    '.' => EMPTY_BLOCK,
+                    _ => cell
+                        .to_digit(16)
+                        .filter(|block| *block < EMPTY_BLOCK as u32)
+                        .ok_or_else(|| D::Error::custom(format!("invalid block {:?}", cell)))?
+                        as u8,
+                };
+            }
+        }
+    }
+    Ok(blocks)
+}